@@ -2,7 +2,7 @@ use std::{fs::File, io::{BufReader, Cursor}, time::Duration};
 use bevy_asset::RenderAssetUsages;
 use bevy::{diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}, prelude::*, render::{render_resource::{Extent3d, TextureDimension}}};
 use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt}};
-use bevy_movie_player::{gv::{self, load_gv, load_gv_on_memory, GVMovie, GVMovieOnMemory, GVMoviePlayer}, image_data_provider::{CompressedImageDataProvider, ImageCreator}, movie_player::{LoopMode}, prelude::*};
+use bevy_movie_player::{gv::{self, load_gv, load_gv_on_memory, GVMovie, GVMoviePlayer}, image_data_provider::{CompressedImageDataProvider, ImageCreator}, movie_player::{LoopMode}, prelude::*};
 
 fn main() {
     App::new()
@@ -40,9 +40,8 @@ pub struct MovieAssets {
   #[asset(path = "test.gv")]
   pub test: Handle<GVMovie>,
 
-  // // if you want to load on memory:
-  // #[asset(path = "test.gv")]
-  // pub test: Handle<GVMovieOnMemory>,
+  // // if you want to load on memory (or memory-mapped), use loader settings:
+  // asset_server.load_with_settings("test.gv", |s: &mut gv::GVLoaderSettings| s.load_mode = LoadMode::OnMemory)
 }
 
 #[derive(Resource)]
@@ -72,7 +71,6 @@ fn setup(
     mut _movie_res: ResMut<MovieRes>,
     mut movie_assets: ResMut<MovieAssets>,
     mut assets: ResMut<Assets<GVMovie>>,
-    // mut assets: ResMut<Assets<GVMovieOnMemory>>,
     // mut asset_server: Res<AssetServer>,
     // time: Res<Time>,
 ) {
@@ -133,7 +131,6 @@ fn update(
     mut movie_res: ResMut<MovieRes>,
     mut movie_assets: ResMut<MovieAssets>,
     mut assets: ResMut<Assets<GVMovie>>,
-    // mut assets: ResMut<Assets<GVMovieOnMemory>>,
     time: Res<Time>,
 ) {
     // skip update to be fps 30 (msec 33)
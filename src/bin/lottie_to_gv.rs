@@ -0,0 +1,88 @@
+//! Bake Lottie JSON into `.gv`
+//!
+//! ```sh
+//! cargo run --release --features lottie,gv-encode --bin lottie-to-gv -- input.json output.gv --size 256x256 --fps 30 --format dxt5
+//! ```
+
+use bevy_movie_player::gv_encode::parse_gv_format;
+use bevy_movie_player::lottie::try_load_lottie;
+use bevy_movie_player::lottie_bake::bake_lottie_to_gv;
+use bevy_movie_player::lottie_bake::LottieBakeSettings;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: lottie-to-gv <input.json> <output.gv> [--size WxH] [--fps N] [--format dxt1|dxt3|dxt5|bc7]";
+
+fn parse_args() -> Result<(String, String, LottieBakeSettings), String> {
+    let mut args = std::env::args().skip(1);
+    let mut paths = vec![];
+    let mut settings = LottieBakeSettings::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--size" => {
+                let v = value()?;
+                let (w, h) = v.split_once('x').ok_or(format!("invalid size: {}", v))?;
+                settings.size = Some((
+                    w.parse().map_err(|_| format!("invalid size: {}", v))?,
+                    h.parse().map_err(|_| format!("invalid size: {}", v))?,
+                ));
+            },
+            "--fps" => {
+                let v = value()?;
+                settings.fps = Some(v.parse().map_err(|_| format!("invalid fps: {}", v))?);
+            },
+            "--format" => {
+                let v = value()?;
+                settings.format = parse_gv_format(&v).ok_or(format!("invalid format: {}", v))?;
+            },
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => paths.push(arg),
+        }
+    }
+
+    match <[String; 2]>::try_from(paths) {
+        Ok([input, output]) => Ok((input, output, settings)),
+        Err(_) => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let (input, output, settings) = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let mut player = match try_load_lottie(&input) {
+        Ok(player) => player,
+        Err(e) => {
+            eprintln!("failed to load {}: {}", input, e);
+            return ExitCode::FAILURE;
+        },
+    };
+    let result = File::create(&output).and_then(|file| {
+        bake_lottie_to_gv(&mut player, BufWriter::new(file), &settings, |done, total| {
+            eprint!("\rframe {}/{}", done, total);
+            let _ = std::io::stderr().flush();
+        })
+    });
+    eprintln!();
+
+    match result {
+        Ok(_) => {
+            eprintln!("baked {} -> {}", input, output);
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("failed to bake {}: {}", input, e);
+            ExitCode::FAILURE
+        },
+    }
+}
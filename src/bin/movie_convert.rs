@@ -0,0 +1,413 @@
+//! Convert movies (ffmpeg video, Lottie, GIF, image sequence, `.gv`) into `.gv` or PNG sequence
+//!
+//! ```sh
+//! cargo run --release --features gv-encode,ffmpeg --bin movie-convert -- input.mp4 output.gv --size 640x360 --fps 30 --format dxt1
+//! cargo run --release --features gv-encode --bin movie-convert -- input.gv frames/ --start 1.0 --end 2.5
+//! ```
+//!
+//! Output ending with `.gv` is encoded as GV, otherwise it is a directory of PNG files.
+//! Input formats: directory (image sequence) and `.gv`, `.json` (`--features lottie`),
+//! `.gif` (`--features gif`) and others by ffmpeg (`--features ffmpeg`).
+
+use bevy::render::render_resource::TextureFormat;
+use bevy_movie_player::blankable_image_data_provider::unpremultiply_bgra;
+use bevy_movie_player::blankable_image_data_provider::BGRAImageFrameProvider;
+use bevy_movie_player::gv::try_load_gv_from_reader;
+use bevy_movie_player::gv_encode::list_image_sequence;
+use bevy_movie_player::gv_encode::load_image_bgra;
+use bevy_movie_player::gv_encode::pad_bgra_to_multiple_of_4;
+use bevy_movie_player::gv_encode::parse_gv_format;
+use bevy_movie_player::gv_encode::GVEncoder;
+use bevy_movie_player::movie_player::MoviePlayer;
+use gv_video::GVFormat;
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "usage: movie-convert <input> <output.gv|output_dir> [--size WxH] [--fps N] [--start SECS] [--end SECS] [--format dxt1|dxt3|dxt5|bc7] [--input-fps N]
+
+  --size       fit into WxH, keeping aspect ratio
+  --fps        output frame rate (default: frame rate of the input)
+  --start/end  trim by time range
+  --format     BC format of .gv (default: dxt5)
+  --input-fps  frame rate of image sequence input (default: 30)";
+
+struct Args {
+    input: String,
+    output: String,
+    size: Option<(u32, u32)>,
+    fps: Option<f32>,
+    start: Option<f64>,
+    end: Option<f64>,
+    format: GVFormat,
+    input_fps: f32,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut paths = vec![];
+    let mut size = None;
+    let mut fps = None;
+    let mut start = None;
+    let mut end = None;
+    let mut format = GVFormat::DXT5;
+    let mut input_fps = 30.0;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--size" => {
+                let v = value()?;
+                let (w, h) = v.split_once('x').ok_or(format!("invalid size: {}", v))?;
+                size = Some((
+                    w.parse().map_err(|_| format!("invalid size: {}", v))?,
+                    h.parse().map_err(|_| format!("invalid size: {}", v))?,
+                ));
+            },
+            "--fps" => {
+                let v = value()?;
+                fps = Some(v.parse().map_err(|_| format!("invalid fps: {}", v))?);
+            },
+            "--start" => {
+                let v = value()?;
+                start = Some(v.parse().map_err(|_| format!("invalid start: {}", v))?);
+            },
+            "--end" => {
+                let v = value()?;
+                end = Some(v.parse().map_err(|_| format!("invalid end: {}", v))?);
+            },
+            "--format" => {
+                let v = value()?;
+                format = parse_gv_format(&v).ok_or(format!("invalid format: {}", v))?;
+            },
+            "--input-fps" => {
+                let v = value()?;
+                input_fps = v.parse().map_err(|_| format!("invalid input fps: {}", v))?;
+            },
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => paths.push(arg),
+        }
+    }
+
+    match <[String; 2]>::try_from(paths) {
+        Ok([input, output]) => Ok(Args { input, output, size, fps, start, end, format, input_fps }),
+        Err(_) => Err(USAGE.to_string()),
+    }
+}
+
+fn other_error<E: ToString>(e: E) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+/// Frames of input, as straight alpha BGRA8
+trait FrameSource {
+    fn get_duration(&self) -> Duration;
+    /// `None` if unknown (e.g. variable frame delays)
+    fn get_fps(&self) -> Option<f32>;
+    fn get_resolution(&self) -> (u32, u32);
+    fn read_frame_at(&mut self, t: Duration) -> std::io::Result<Vec<u8>>;
+}
+
+/// Any movie player, read by seeking while paused
+struct PlayerSource<P>(P);
+
+impl<P: MoviePlayer + BGRAImageFrameProvider> PlayerSource<P> {
+    fn new(mut player: P) -> std::io::Result<Self> {
+        if player.get_frame_texture_format() != TextureFormat::Bgra8UnormSrgb {
+            return Err(other_error(format!("frames must be BGRA8, not {:?}", player.get_frame_texture_format())));
+        }
+        player.play();
+        player.pause();
+        Ok(Self(player))
+    }
+}
+
+impl<P: MoviePlayer + BGRAImageFrameProvider> FrameSource for PlayerSource<P> {
+    fn get_duration(&self) -> Duration {
+        self.0.get_duration()
+    }
+
+    fn get_fps(&self) -> Option<f32> {
+        self.0.get_metadata().frame_rate
+    }
+
+    fn get_resolution(&self) -> (u32, u32) {
+        self.0.get_resolution()
+    }
+
+    fn read_frame_at(&mut self, t: Duration) -> std::io::Result<Vec<u8>> {
+        let _ = self.0.seek(t);
+        let mut frame = self.0.get_paused_frame_bgra()
+            .ok_or_else(|| other_error(format!("failed to read frame at {:?}", t)))?;
+        if self.0.is_premultiplied_alpha() {
+            unpremultiply_bgra(&mut frame);
+        }
+        Ok(frame)
+    }
+}
+
+struct ImageSequenceSource {
+    paths: Vec<PathBuf>,
+    fps: f32,
+    resolution: (u32, u32),
+}
+
+impl ImageSequenceSource {
+    fn new(dir: &Path, fps: f32) -> std::io::Result<Self> {
+        let paths = list_image_sequence(dir)?;
+        let first = paths.first().ok_or_else(|| other_error(format!("no images in {}", dir.display())))?;
+        let (_, width, height) = load_image_bgra(first)?;
+        Ok(Self {
+            paths,
+            fps,
+            resolution: (width, height),
+        })
+    }
+}
+
+impl FrameSource for ImageSequenceSource {
+    fn get_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.paths.len() as f64 / self.fps as f64)
+    }
+
+    fn get_fps(&self) -> Option<f32> {
+        Some(self.fps)
+    }
+
+    fn get_resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    fn read_frame_at(&mut self, t: Duration) -> std::io::Result<Vec<u8>> {
+        let index = ((t.as_secs_f64() * self.fps as f64) as usize).min(self.paths.len() - 1);
+        let path = &self.paths[index];
+        let (frame, width, height) = load_image_bgra(path)?;
+        if (width, height) != self.resolution {
+            return Err(other_error(format!(
+                "{}: {}x{} differs from {}x{}",
+                path.display(), width, height, self.resolution.0, self.resolution.1,
+            )));
+        }
+        Ok(frame)
+    }
+}
+
+/// GIF frames (composited) with start times
+#[cfg(feature = "gif")]
+struct GifSource {
+    frames: Vec<(Duration, Vec<u8>)>,
+    duration: Duration,
+    resolution: (u32, u32),
+}
+
+#[cfg(feature = "gif")]
+impl GifSource {
+    fn new(path: &Path) -> std::io::Result<Self> {
+        use image::AnimationDecoder;
+
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::BufReader::new(File::open(path)?))
+            .map_err(other_error)?;
+        let mut frames = vec![];
+        let mut t = Duration::ZERO;
+        let mut resolution = (0, 0);
+        for frame in decoder.into_frames() {
+            let frame = frame.map_err(other_error)?;
+            let delay = Duration::from(frame.delay());
+            let buffer = frame.into_buffer();
+            resolution = buffer.dimensions();
+            let mut data = buffer.into_raw();
+            for px in data.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+            frames.push((t, data));
+            // NOTE: same as browsers, zero delay is treated as 100ms
+            t += if delay.is_zero() { Duration::from_millis(100) } else { delay };
+        }
+        if frames.is_empty() {
+            return Err(other_error(format!("no frames in {}", path.display())));
+        }
+        Ok(Self {
+            frames,
+            duration: t,
+            resolution,
+        })
+    }
+}
+
+#[cfg(feature = "gif")]
+impl FrameSource for GifSource {
+    fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// average frame rate
+    fn get_fps(&self) -> Option<f32> {
+        Some((self.frames.len() as f64 / self.duration.as_secs_f64()) as f32)
+    }
+
+    fn get_resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    fn read_frame_at(&mut self, t: Duration) -> std::io::Result<Vec<u8>> {
+        let index = self.frames.partition_point(|(start, _)| *start <= t).max(1) - 1;
+        Ok(self.frames[index].1.clone())
+    }
+}
+
+fn open_source(args: &Args) -> std::io::Result<Box<dyn FrameSource>> {
+    let path = Path::new(&args.input);
+    if path.is_dir() {
+        return Ok(Box::new(ImageSequenceSource::new(path, args.input_fps)?));
+    }
+    if !path.exists() {
+        return Err(other_error(format!("{} not found", args.input)));
+    }
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "gv" => {
+            let player = try_load_gv_from_reader(BufReader::new(File::open(path)?)).map_err(other_error)?;
+            Ok(Box::new(PlayerSource::new(player)?))
+        },
+        #[cfg(feature = "lottie")]
+        "json" => {
+            let resource_path = path.parent().unwrap_or(Path::new(""));
+            let mut player = bevy_movie_player::lottie::try_load_lottie_from_data(std::fs::read(path)?, args.input.as_str(), resource_path)
+                .map_err(other_error)?;
+            // render at the output size for sharp result
+            if let Some((width, height)) = args.size {
+                player.set_render_size(width, height);
+            }
+            Ok(Box::new(PlayerSource::new(player)?))
+        },
+        #[cfg(feature = "gif")]
+        "gif" => Ok(Box::new(GifSource::new(path)?)),
+        #[cfg(feature = "ffmpeg")]
+        _ => {
+            let player = bevy_movie_player::ffmpeg::try_load_movie(&args.input).map_err(other_error)?;
+            Ok(Box::new(PlayerSource::new(player)?))
+        },
+        #[cfg(not(feature = "ffmpeg"))]
+        _ => Err(other_error(format!(
+            "unsupported input: {} (enable features lottie / gif / ffmpeg)", args.input,
+        ))),
+    }
+}
+
+fn fit_size(size: (u32, u32), bounds: (u32, u32)) -> (u32, u32) {
+    let scale = (bounds.0 as f64 / size.0 as f64).min(bounds.1 as f64 / size.1 as f64);
+    (
+        ((size.0 as f64 * scale).round() as u32).max(1),
+        ((size.1 as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Resize BGRA8 (channels are treated the same, so it works as RGBA)
+fn resize_bgra(frame: Vec<u8>, from: (u32, u32), to: (u32, u32)) -> Vec<u8> {
+    if from == to {
+        return frame;
+    }
+    let image = image::RgbaImage::from_raw(from.0, from.1, frame).expect("frame size mismatch");
+    image::imageops::resize(&image, to.0, to.1, image::imageops::FilterType::Triangle).into_raw()
+}
+
+enum Output {
+    Gv(GVEncoder<BufWriter<File>>),
+    Png(PathBuf),
+}
+
+impl Output {
+    fn create(path: &str, resolution: (u32, u32), fps: f32, format: GVFormat) -> std::io::Result<Self> {
+        if path.to_lowercase().ends_with(".gv") {
+            let (width, height) = (resolution.0.div_ceil(4) * 4, resolution.1.div_ceil(4) * 4);
+            let writer = BufWriter::new(File::create(path)?);
+            Ok(Self::Gv(GVEncoder::new(writer, width, height, fps, format)?))
+        } else {
+            std::fs::create_dir_all(path)?;
+            Ok(Self::Png(PathBuf::from(path)))
+        }
+    }
+
+    fn add_frame(&mut self, index: u32, mut frame: Vec<u8>, resolution: (u32, u32)) -> std::io::Result<()> {
+        match self {
+            Self::Gv(encoder) => {
+                let (frame, ..) = pad_bgra_to_multiple_of_4(&frame, resolution.0, resolution.1);
+                encoder.add_frame_bgra(&frame)
+            },
+            Self::Png(dir) => {
+                for px in frame.chunks_exact_mut(4) {
+                    px.swap(0, 2);
+                }
+                let path = dir.join(format!("frame_{:05}.png", index));
+                image::save_buffer(path, &frame, resolution.0, resolution.1, image::ExtendedColorType::Rgba8)
+                    .map_err(other_error)
+            },
+        }
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Gv(encoder) => encoder.finish().map(|_| ()),
+            Self::Png(_) => Ok(()),
+        }
+    }
+}
+
+fn convert(args: &Args) -> std::io::Result<()> {
+    let mut source = open_source(args)?;
+    let source_resolution = source.get_resolution();
+    let resolution = match args.size {
+        Some(size) => fit_size(source_resolution, size),
+        None => source_resolution,
+    };
+    let fps = args.fps.or(source.get_fps()).unwrap_or(30.0);
+    let duration = source.get_duration().as_secs_f64();
+    let start = args.start.unwrap_or(0.0).clamp(0.0, duration);
+    let end = args.end.unwrap_or(duration).clamp(start, duration);
+    let frame_count = (((end - start) * fps as f64).round() as u32).max(1);
+
+    eprintln!(
+        "{} ({}x{}, {:.3}s) -> {} ({}x{}, {}fps, {} frames)",
+        args.input, source_resolution.0, source_resolution.1, duration,
+        args.output, resolution.0, resolution.1, fps, frame_count,
+    );
+
+    let mut output = Output::create(&args.output, resolution, fps, args.format)?;
+    for i in 0..frame_count {
+        let t = Duration::from_secs_f64(start + i as f64 / fps as f64);
+        let frame = source.read_frame_at(t)?;
+        let frame = resize_bgra(frame, source_resolution, resolution);
+        output.add_frame(i, frame, resolution)?;
+        eprint!("\rframe {}/{}", i + 1, frame_count);
+        let _ = std::io::stderr().flush();
+    }
+    eprintln!();
+    output.finish()
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    match convert(&args) {
+        Ok(()) => {
+            eprintln!("converted {} -> {}", args.input, args.output);
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("failed to convert {}: {}", args.input, e);
+            ExitCode::FAILURE
+        },
+    }
+}
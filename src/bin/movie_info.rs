@@ -0,0 +1,201 @@
+//! Print information of movies, and optionally validate all frames (exits with failure on errors)
+//!
+//! ```sh
+//! cargo run --release --features gv,lottie,ffmpeg --bin movie-info -- assets/test.gv assets/test.json --validate
+//! ```
+//!
+//! Backends follow the enabled features: `.gv` (`--features gv`), `.json` (`--features lottie`),
+//! and others by ffmpeg (`--features ffmpeg`).
+
+#![cfg_attr(not(any(feature = "gv", feature = "lottie", feature = "ffmpeg")), allow(dead_code, unused_imports))]
+
+use bevy_movie_player::movie_player::MovieMetadata;
+use bevy_movie_player::movie_player::MoviePlayer;
+
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: movie-info <file>... [--validate]
+
+  --validate  decode all frames, and exit with failure on errors";
+
+/// Print common info of a player
+fn print_player_info<P: MoviePlayer>(player: &P) {
+    let (width, height) = player.get_resolution();
+    println!("  duration:   {:.3}s", player.get_duration().as_secs_f64());
+    println!("  resolution: {}x{}", width, height);
+    print_metadata(&player.get_metadata());
+}
+
+fn print_metadata(metadata: &MovieMetadata) {
+    if let Some(frame_rate) = metadata.frame_rate {
+        println!("  fps:        {}", frame_rate);
+    }
+    if let Some(codec) = &metadata.codec {
+        println!("  codec:      {}", codec);
+    }
+    if let Some(container) = &metadata.container {
+        println!("  container:  {}", container);
+    }
+    if let Some(bitrate) = metadata.bitrate {
+        println!("  bitrate:    {} kbps", bitrate / 1000);
+    }
+    if let Some(bit_depth) = metadata.bit_depth {
+        println!("  bit depth:  {}", bit_depth);
+    }
+    if metadata.get_rotation_degrees() != 0.0 {
+        println!("  rotation:   {}", metadata.get_rotation_degrees());
+    }
+    for chapter in &metadata.chapters {
+        println!(
+            "  chapter:    {:.3}s - {:.3}s {}",
+            chapter.start.as_secs_f64(), chapter.end.as_secs_f64(), chapter.title.as_deref().unwrap_or(""),
+        );
+    }
+    for (key, value) in &metadata.tags {
+        println!("  tag:        {} = {}", key, value);
+    }
+}
+
+#[cfg(feature = "gv")]
+fn inspect_gv(path: &Path, validate: bool) -> Result<Vec<String>, String> {
+    use bevy_movie_player::gv::check_gv_integrity;
+    use bevy_movie_player::gv::load_gv_from_reader;
+    use bevy_movie_player::gv::validate_gv_frames;
+
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut reader = std::io::BufReader::new(file);
+    check_gv_integrity(&mut reader).map_err(|e| e.to_string())?;
+    // NOTE: gv_video panics on broken header
+    let mut player = std::panic::catch_unwind(|| load_gv_from_reader(reader))
+        .map_err(|_| "failed to read GV header".to_string())?;
+    print_player_info(&player);
+    println!("  frames:     {}", player.gv.get_frame_count());
+    println!("  GVFormat:   {:?}", player.gv.get_format());
+
+    Ok(if validate { validate_gv_frames(&mut player.gv) } else { vec![] })
+}
+
+#[cfg(feature = "lottie")]
+fn inspect_lottie(path: &Path, validate: bool) -> Result<Vec<String>, String> {
+    use bevy_movie_player::lottie::try_load_lottie_from_data;
+    use bevy_movie_player::lottie::validate_lottie;
+
+    let json_data = std::fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice::<serde_json::Value>(&json_data).map_err(|e| format!("invalid JSON: {}", e))?;
+    let resource_path = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    let mut player = try_load_lottie_from_data(json_data, "", resource_path).map_err(|e| e.to_string())?;
+    print_player_info(&player);
+    let (width, height) = player.get_intrinsic_size();
+    println!("  intrinsic:  {}x{}", width, height);
+    if let Some(name) = &player.info.name {
+        println!("  name:       {}", name);
+    }
+    let frame_count = (player.get_duration().as_secs_f64() * player.get_metadata().frame_rate.unwrap_or(0.0) as f64).round() as u32;
+    println!("  frames:     {}", frame_count);
+    for marker in &player.info.markers {
+        println!("  marker:     {} (frame {} - {})", marker.name, marker.start_frame, marker.end_frame());
+    }
+
+    Ok(if validate { validate_lottie(&mut player) } else { vec![] })
+}
+
+#[cfg(feature = "ffmpeg")]
+fn inspect_ffmpeg(path: &Path, validate: bool) -> Result<Vec<String>, String> {
+    use bevy_movie_player::ffmpeg::read_metadata;
+    use bevy_movie_player::ffmpeg::read_stream_infos;
+    use bevy_movie_player::ffmpeg::validate_video_frames;
+
+    let metadata = read_metadata(path).map_err(|e| e.to_string())?;
+    print_metadata(&metadata);
+    for stream in read_stream_infos(path).map_err(|e| e.to_string())? {
+        println!(
+            "  stream #{}:  {} {}{}{}{}",
+            stream.index,
+            stream.media_type,
+            stream.codec,
+            stream.duration.map(|d| format!(", {:.3}s", d.as_secs_f64())).unwrap_or_default(),
+            stream.frame_count.map(|n| format!(", {} frames", n)).unwrap_or_default(),
+            stream.language.map(|l| format!(", {}", l)).unwrap_or_default(),
+        );
+    }
+
+    if !validate {
+        return Ok(vec![]);
+    }
+    let validation = validate_video_frames(path).map_err(|e| e.to_string())?;
+    println!("  decoded:    {} frames", validation.decoded_frames);
+    if validation.decoded_frames == 0 {
+        return Ok(vec!["no frames decoded".to_string()]);
+    }
+    Ok(validation.errors)
+}
+
+/// Returns errors of frames, or `Err` if it can't be opened
+fn inspect(path: &Path, validate: bool) -> Result<Vec<String>, String> {
+    if !path.is_file() {
+        return Err("file not found".to_string());
+    }
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        #[cfg(feature = "gv")]
+        "gv" => inspect_gv(path, validate),
+        #[cfg(feature = "lottie")]
+        "json" => inspect_lottie(path, validate),
+        #[cfg(feature = "ffmpeg")]
+        _ => inspect_ffmpeg(path, validate),
+        #[cfg(not(feature = "ffmpeg"))]
+        _ => {
+            let _ = validate;
+            Err("unsupported format (enable features gv / lottie / ffmpeg)".to_string())
+        },
+    }
+}
+
+fn main() -> ExitCode {
+    let mut paths = vec![];
+    let mut validate = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--validate" => validate = true,
+            "-h" | "--help" => {
+                eprintln!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            },
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+
+    let mut failed = false;
+    for path in &paths {
+        println!("{}", path);
+        match inspect(Path::new(path), validate) {
+            Ok(errors) if errors.is_empty() => {
+                if validate {
+                    println!("  valid");
+                }
+            },
+            Ok(errors) => {
+                failed = true;
+                for error in &errors {
+                    println!("  error:      {}", error);
+                }
+                eprintln!("{}: {} errors", path, errors.len());
+            },
+            Err(e) => {
+                failed = true;
+                eprintln!("{}: {}", path, e);
+            },
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
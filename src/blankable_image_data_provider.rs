@@ -30,6 +30,68 @@ pub trait BGRAImageFrameProvider {
     fn get_paused_frame_bgra(&mut self) -> Option<Vec<u8>>;
     fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>>;
     fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>>;
+    /// frame at the current (paused) position for `FrameGrabber`, which must not disturb playback.
+    /// override it if reading the paused frame moves decoder state (e.g. ffmpeg)
+    fn get_grabbed_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.get_paused_frame_bgra()
+    }
+    /// texture format of frames
+    fn get_frame_texture_format(&self) -> TextureFormat {
+        TextureFormat::Bgra8UnormSrgb
+    }
+    /// convert a BGRA8 blank pixel into `get_frame_texture_format()`. override it with the format
+    fn get_blank_pixel(&self, bgra: &[u8]) -> Vec<u8> {
+        bgra.to_vec()
+    }
+    /// frames have premultiplied alpha (e.g. rlottie output)
+    fn is_premultiplied_alpha(&self) -> bool {
+        false
+    }
+    /// un-premultiply premultiplied frames in `get_image_data`, for default (straight) alpha blending.
+    /// return false to keep them for premultiplied blending (e.g. `AlphaMode::Premultiplied`)
+    fn should_unpremultiply_alpha(&self) -> bool {
+        true
+    }
+}
+
+/// Convert premultiplied BGRA8 (or RGBA8) into straight alpha, in place
+pub fn unpremultiply_bgra(data: &mut [u8]) {
+    for px in data.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        if a == 0 {
+            px[0] = 0;
+            px[1] = 0;
+            px[2] = 0;
+        } else if a < 255 {
+            for c in &mut px[0..3] {
+                *c = ((*c as u32 * 255 + a / 2) / a).min(255) as u8;
+            }
+        }
+    }
+}
+
+fn to_straight_alpha<T: BGRAImageFrameProvider>(provider: &T, mut frame: Vec<u8>) -> Vec<u8> {
+    if provider.is_premultiplied_alpha()
+        && provider.should_unpremultiply_alpha()
+        && provider.get_frame_texture_format() == TextureFormat::Bgra8UnormSrgb
+    {
+        unpremultiply_bgra(&mut frame);
+    }
+    frame
+}
+
+/// 1x1 BGRA8 blanks into the frame format, so the texture format does not flip on blanking
+fn to_frame_format<T: BGRAImageFrameProvider>(provider: &T, image_data: ImageData) -> ImageData {
+    let format = provider.get_frame_texture_format();
+    if image_data.format == TextureFormat::Bgra8UnormSrgb && format != image_data.format && image_data.resolution == (1, 1) {
+        ImageData {
+            data: provider.get_blank_pixel(&image_data.data),
+            format,
+            resolution: (1, 1),
+        }
+    } else {
+        image_data
+    }
 }
 
 pub trait CompressedImageFrameProvider {
@@ -133,42 +195,42 @@ where
                 };
                 let frame_data = if let Some(frame) = frame_or_not {
                     Some(ImageData {
-                        data: frame,
-                        format: TextureFormat::Bgra8UnormSrgb,
+                        data: to_straight_alpha(self, frame),
+                        format: self.get_frame_texture_format(),
                         resolution: self.get_resolution(),
                     })
                 } else {
                     None
                 };
                 
-                self.get_blank_frame_bgra(self.get_state(), frame_data)
+                to_frame_format(self, self.get_blank_frame_bgra(self.get_state(), frame_data))
             }
             PlayingState::Paused => {
                 // FIXME: slow? need cached for first and last frame?
                 let last_frame = self.get_paused_frame_bgra();
                 let last_frame_data = if let Some(frame) = last_frame {
                     Some(ImageData {
-                        data: frame,
-                        format: TextureFormat::Bgra8UnormSrgb,
+                        data: to_straight_alpha(self, frame),
+                        format: self.get_frame_texture_format(),
                         resolution: self.get_resolution(),
                     })
                 } else {
                     None
                 };
                 
-                self.get_blank_frame_bgra(self.get_state(), last_frame_data)
+                to_frame_format(self, self.get_blank_frame_bgra(self.get_state(), last_frame_data))
             }
             PlayingState::Playing => {
                 let frame = self.get_playing_frame_bgra();
                 let frame_data = if let Some(frame) = frame {
                     ImageData {
-                        data: frame,
-                        format: TextureFormat::Bgra8UnormSrgb,
+                        data: to_straight_alpha(self, frame),
+                        format: self.get_frame_texture_format(),
                         resolution: self.get_resolution(),
                     }
                 } else {
                     // WORKAROUND
-                    self.get_blank_frame_bgra(self.get_state(), None)
+                    to_frame_format(self, self.get_blank_frame_bgra(self.get_state(), None))
                 };
                 frame_data
             }
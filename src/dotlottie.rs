@@ -0,0 +1,259 @@
+use bevy::asset::io::Reader;
+use bevy::asset::AssetLoader;
+use bevy::asset::LoadContext;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+
+use crate::lottie::embed_images;
+use crate::lottie::try_load_lottie_from_data;
+use crate::lottie::LottieMovie;
+use crate::lottie::LottieMovieLoaderSettings;
+
+use std::io::Cursor;
+use std::io::Read;
+
+/// dotLottie (`.lottie`) archive, which contains multiple animations, themes and images.
+///
+/// Each animation is also loaded as labeled sub asset, e.g. `anim.lottie#idle`.
+#[derive(Asset, TypePath, Debug)]
+pub struct DotLottie {
+    pub manifest: DotLottieManifest,
+    /// same order as `manifest.animations`
+    #[dependency]
+    pub animations: Vec<Handle<LottieMovie>>,
+    /// `(id, JSON)` of themes. NOTE: not applied, because rlottie doesn't support slots
+    pub themes: Vec<(String, String)>,
+}
+
+impl DotLottie {
+    pub fn get_animation(&self, id: &str) -> Option<Handle<LottieMovie>> {
+        let index = self.manifest.animations.iter().position(|a| a.id == id)?;
+        self.animations.get(index).cloned()
+    }
+
+    /// `activeAnimationId` (v1) / `initial.animation` (v2), or the first animation
+    pub fn get_default_animation(&self) -> Option<Handle<LottieMovie>> {
+        self.manifest.active_animation_id.as_deref()
+            .and_then(|id| self.get_animation(id))
+            .or_else(|| self.animations.first().cloned())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DotLottieManifest {
+    /// `"1"` or `"2"` (dotLottie spec version)
+    pub version: Option<String>,
+    pub generator: Option<String>,
+    pub active_animation_id: Option<String>,
+    pub animations: Vec<DotLottieAnimationInfo>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DotLottieAnimationInfo {
+    pub id: String,
+    /// v1 only
+    pub speed: Option<f64>,
+    /// v1 only
+    pub looping: Option<bool>,
+}
+
+fn invalid_data<E: ToString>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Contents of dotLottie archive, animations have images embedded
+pub struct DotLottieContents {
+    pub manifest: DotLottieManifest,
+    /// `(id, Lottie JSON)`
+    pub animations: Vec<(String, Vec<u8>)>,
+    pub themes: Vec<(String, String)>,
+}
+
+impl DotLottieManifest {
+    fn from_json(json: &serde_json::Value) -> Self {
+        let get_str = |v: &serde_json::Value, key: &str| v.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let animations = json.get("animations")
+            .and_then(|v| v.as_array())
+            .map(|animations| animations.iter()
+                .filter_map(|a| Some(DotLottieAnimationInfo {
+                    id: get_str(a, "id")?,
+                    speed: a.get("speed").and_then(|v| v.as_f64()),
+                    looping: a.get("loop").and_then(|v| v.as_bool()),
+                }))
+                .collect())
+            .unwrap_or_default();
+
+        Self {
+            version: json.get("version").map(|v| match v.as_str() {
+                Some(s) => s.to_string(),
+                None => v.to_string(),
+            }),
+            generator: get_str(json, "generator"),
+            active_animation_id: get_str(json, "activeAnimationId")
+                .or_else(|| json.get("initial").and_then(|v| get_str(v, "animation"))),
+            animations,
+        }
+    }
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Option<Vec<u8>> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// Read dotLottie archive (v1: `animations/`, `images/`, v2: `a/`, `i/`, `t/`)
+pub fn read_dot_lottie(bytes: Vec<u8>) -> Result<DotLottieContents, std::io::Error> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(invalid_data)?;
+
+    let manifest_json = read_entry(&mut archive, "manifest.json")
+        .ok_or_else(|| invalid_data("manifest.json not found in dotLottie"))?;
+    let manifest_json: serde_json::Value = serde_json::from_slice(&manifest_json).map_err(invalid_data)?;
+    let mut manifest = DotLottieManifest::from_json(&manifest_json);
+
+    let names: Vec<String> = archive.file_names().map(|s| s.to_string()).collect();
+    let find_in = |dirs: &[&str], id: &str, ext: &str| dirs.iter()
+        .map(|dir| format!("{}/{}.{}", dir, id, ext))
+        .find(|name| names.contains(name));
+
+    // no animation list in manifest: use all JSON in animation directories
+    if manifest.animations.is_empty() {
+        manifest.animations = names.iter()
+            .filter_map(|name| {
+                let id = name.strip_prefix("animations/").or_else(|| name.strip_prefix("a/"))?;
+                Some(DotLottieAnimationInfo {
+                    id: id.strip_suffix(".json")?.to_string(),
+                    ..default()
+                })
+            })
+            .collect();
+    }
+
+    let mut animations = vec![];
+    for info in &manifest.animations {
+        let Some(name) = find_in(&["a", "animations"], &info.id, "json") else {
+            return Err(invalid_data(format!("animation {} not found in dotLottie", info.id)));
+        };
+        let json_data = read_entry(&mut archive, &name).unwrap_or_default();
+        let mut json: serde_json::Value = serde_json::from_slice(&json_data).map_err(invalid_data)?;
+
+        embed_images(&mut json, |u, p| {
+            let file_name = p.rsplit('/').next().unwrap_or(p);
+            let candidates = [
+                format!("{}{}", u, p).trim_start_matches('/').to_string(),
+                format!("i/{}", file_name),
+                format!("images/{}", file_name),
+            ];
+            candidates.iter().find_map(|name| read_entry(&mut archive, name))
+        });
+        animations.push((info.id.clone(), serde_json::to_vec(&json).map_err(invalid_data)?));
+    }
+
+    let themes = names.iter()
+        .filter_map(|name| {
+            let id = name.strip_prefix("t/").or_else(|| name.strip_prefix("themes/"))?;
+            let id = id.strip_suffix(".json").or_else(|| id.strip_suffix(".lss"))?;
+            let bytes = read_entry(&mut archive, name)?;
+            Some((id.to_string(), String::from_utf8(bytes).ok()?))
+        })
+        .collect();
+
+    Ok(DotLottieContents {
+        manifest,
+        animations,
+        themes,
+    })
+}
+
+#[derive(Default, TypePath)]
+pub struct DotLottieLoader;
+
+impl AssetLoader for DotLottieLoader {
+    type Asset = DotLottie;
+    type Settings = LottieMovieLoaderSettings;
+    type Error = std::io::Error;
+
+    fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        let settings = settings.clone();
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let contents = read_dot_lottie(bytes)?;
+
+            let mut animations = vec![];
+            for (id, json_data) in contents.animations {
+                let cache_key = format!("{}#{}", load_context.path(), id);
+                let mut player = try_load_lottie_from_data(json_data, cache_key, "")
+                    .map_err(|e| std::io::Error::new(e.kind(), format!("animation {}: {}", id, e)))?;
+                player.apply_loader_settings(&settings);
+                animations.push(load_context.add_labeled_asset(id, LottieMovie::from(player)));
+            }
+
+            Ok(DotLottie {
+                manifest: contents.manifest,
+                animations,
+                themes: contents.themes,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lottie"]
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn create_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn read_archive() {
+        let animation = br#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":4,"h":4,"layers":[],
+            "assets":[{"id":"image_0","w":1,"h":1,"u":"/images/","p":"img_0.png","e":0}]}"#;
+        let bytes = create_archive(&[
+            ("manifest.json", br#"{"version":"1","activeAnimationId":"b","animations":[{"id":"a"},{"id":"b","speed":2}]}"#),
+            ("animations/a.json", animation),
+            ("animations/b.json", animation),
+            ("images/img_0.png", b"\x89PNG"),
+        ]);
+
+        let contents = read_dot_lottie(bytes).unwrap();
+        assert_eq!(contents.manifest.active_animation_id, Some("b".to_string()));
+        assert_eq!(contents.manifest.animations[1].speed, Some(2.0));
+        assert_eq!(contents.animations.len(), 2);
+
+        let json: serde_json::Value = serde_json::from_slice(&contents.animations[0].1).unwrap();
+        assert_eq!(json["assets"][0]["e"], 1);
+        assert_eq!(json["assets"][0]["p"], "data:image/png;base64,iVBORw==");
+    }
+
+    #[test]
+    fn missing_manifest() {
+        let bytes = create_archive(&[("a/a.json", b"{}")]);
+        assert!(read_dot_lottie(bytes).is_err());
+    }
+
+    #[test]
+    fn invalid_animation() {
+        // loader returns this error instead of panicking
+        assert!(try_load_lottie_from_data(b"not json".to_vec(), "invalid_animation", "").is_err());
+    }
+}
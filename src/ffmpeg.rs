@@ -5,19 +5,31 @@ use bevy::prelude::*;
 use bevy::render::render_resource::TextureFormat;
 use bevy::tasks::ConditionalSendFuture;
 use derivative::Derivative;
+use serde::Deserialize;
+use serde::Serialize;
 // use ndarray::s;
 // use ndarray::stack;
 // use ndarray::Array;
 // use ndarray::ArrayBase;
 // use ndarray::Dim;
 // use ndarray::OwnedRepr;
+use video_rs::ffmpeg;
 use video_rs::Decoder;
 use video_rs::Url;
 
 use crate::blankable_image_data_provider::BGRAImageFrameProvider;
 use crate::blankable_image_data_provider::BlankMode;
 use crate::blankable_image_data_provider::Blankable;
+use crate::ffmpeg_hdr::HdrDecoder;
+use crate::hdr::FrameOutputFormat;
+use crate::hdr::ToneMapping;
+use crate::movie_format::default_ffmpeg_extensions;
+use crate::movie_format::MovieAssetRoot;
+use crate::subtitle::SubtitleCue;
+use crate::subtitle::SubtitleTrack;
 use crate::movie_player::ImageData;
+use crate::movie_player::MovieChapter;
+use crate::movie_player::MovieMetadata;
 use crate::movie_player::MoviePlayerStateController;
 // use crate::movie_player::LoadMode;
 use crate::movie_player::PlayingState;
@@ -27,6 +39,7 @@ use std::io::BufReader;
 use std::io::Read;
 use std::io::Seek;
 use std::path::Path;
+use std::path::PathBuf;
 use std::time::Duration;
 use std::fmt;
 
@@ -35,12 +48,24 @@ use std::fmt;
 #[derive(Derivative, Asset, TypePath)]
 #[derivative(Debug)]
 pub struct FFmpegMoviePlayer {
+    /// 8-bit BGRA decoder (`None` while `hdr_decoder` is used)
     #[derivative(Debug="ignore")]
-    pub decoder: Decoder,
+    pub decoder: Option<Decoder>,
     #[derivative(Debug="ignore")]
     pub decoder_frame_number: usize,
     #[derivative(Debug="ignore")]
     pub cached_frame: Option<Vec<u8>>,
+    pub metadata: MovieMetadata,
+    path: Option<PathBuf>,
+    duration: Duration,
+    resolution: (u32, u32),
+    frame_rate: f32,
+    /// used instead of `decoder` for high bit depth output
+    #[derivative(Debug="ignore")]
+    hdr_decoder: Option<HdrDecoder>,
+    /// separate decoder for `FrameGrabber`, not to move the playback decoder
+    #[derivative(Debug="ignore")]
+    grab_decoder: Option<HdrDecoder>,
     #[derivative(Debug="ignore")]
     state_controller: MoviePlayerStateController,
     #[derivative(Debug="ignore")]
@@ -52,74 +77,536 @@ pub struct FFmpegMoviePlayer {
 pub struct FFmpegMovie {
     #[derivative(Debug="ignore")]
     pub player: FFmpegMoviePlayer,
+    /// embedded text subtitle streams (labeled `subtitle0`, `subtitle1`, ...)
+    #[dependency]
+    pub subtitles: Vec<Handle<SubtitleTrack>>,
 }
 
 impl From<FFmpegMoviePlayer> for FFmpegMovie {
     fn from(player: FFmpegMoviePlayer) -> Self {
         FFmpegMovie {
             player,
+            subtitles: vec![],
         }
     }
 }
 
 /// Load movie from file path.
 pub fn load_movie(path: &str) -> FFmpegMoviePlayer {
-    let path_ = std::path::Path::new(path);
-    let decoder = Decoder::new(path_).expect("failed to create decoder");
+    try_load_movie(path).expect("failed to create decoder")
+}
 
-    FFmpegMoviePlayer {
-        decoder,
-        decoder_frame_number: 0,
-        cached_frame: None,
-        state_controller: MoviePlayerStateController::default(),
-        blank_mode: BlankMode::default(),
-    }
+/// Load movie from file path, or error if it can't be opened
+pub fn try_load_movie<P: AsRef<Path>>(path: P) -> Result<FFmpegMoviePlayer, video_rs::Error> {
+    let path_ = path.as_ref();
+    let decoder = Decoder::new(path_)?;
+    let metadata = read_metadata(path_).unwrap_or_else(|e| {
+        warn!("Failed to read metadata: {}", e);
+        MovieMetadata::default()
+    });
+
+    Ok(FFmpegMoviePlayer::new(decoder, metadata, Some(path_.to_path_buf())))
 }
 
 /// Load movie from url.
+///
+/// NOTE: this blocks until the stream is opened. Use `stream://` asset source
+/// (`ffmpeg_stream::StreamAssetSourcePlugin`) for non-blocking live streams.
 pub fn load_movie_from_url(url: &str) -> FFmpegMoviePlayer {
     let source = url
             .parse::<Url>()
             .unwrap();
     let decoder = Decoder::new(source).expect("failed to create decoder");
+    let metadata = MovieMetadata {
+        frame_rate: Some(decoder.frame_rate()),
+        ..Default::default()
+    };
+
+    FFmpegMoviePlayer::new(decoder, metadata, None)
+}
+
+impl FFmpegMoviePlayer {
+    fn new(decoder: Decoder, metadata: MovieMetadata, path: Option<PathBuf>) -> Self {
+        let duration = decoder.duration()
+            .map(|time| Duration::from_secs_f64(time.as_secs_f64().max(0.0)))
+            .unwrap_or_else(|e| {
+                warn!("Failed to read duration: {}", e);
+                Duration::ZERO
+            });
+        Self {
+            duration,
+            resolution: decoder.size(),
+            frame_rate: decoder.frame_rate(),
+            decoder: Some(decoder),
+            decoder_frame_number: 0,
+            cached_frame: None,
+            metadata,
+            path,
+            hdr_decoder: None,
+            grab_decoder: None,
+            state_controller: MoviePlayerStateController::default(),
+            blank_mode: BlankMode::default(),
+        }
+    }
+
+    /// Change output texture format. `Rgba16Float` / `Rgb10a2Unorm` keep 10-bit (or more) precision of the source.
+    ///
+    /// Only one of the 8-bit and high bit depth decoders is kept open.
+    pub fn set_output_format(&mut self, output_format: FrameOutputFormat) -> Result<(), ffmpeg::Error> {
+        if output_format == self.get_output_format() {
+            return Ok(());
+        }
+        let Some(path) = &self.path else {
+            warn!("Output format can be changed only for files");
+            return Err(ffmpeg::Error::InvalidData);
+        };
+        if output_format == FrameOutputFormat::Bgra8 {
+            let decoder = Decoder::new(path.as_path()).map_err(|e| {
+                warn!("Failed to reopen decoder: {}", e);
+                ffmpeg::Error::InvalidData
+            })?;
+            self.decoder = Some(decoder);
+            self.hdr_decoder = None;
+        } else if let Some(hdr_decoder) = &mut self.hdr_decoder {
+            hdr_decoder.output_format = output_format;
+        } else {
+            self.hdr_decoder = Some(HdrDecoder::new(path, output_format)?);
+            self.decoder = None;
+        }
+        // restart decoding with new decoder
+        self.decoder_frame_number = usize::MAX;
+        self.cached_frame = None;
+        Ok(())
+    }
+
+    pub fn get_output_format(&self) -> FrameOutputFormat {
+        self.hdr_decoder.as_ref().map(|d| d.output_format).unwrap_or_default()
+    }
+
+    /// Tone mapping for `Rgba16Float` output (set output format first)
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping) {
+        if let Some(hdr_decoder) = &mut self.hdr_decoder {
+            hdr_decoder.tone_mapping = tone_mapping;
+        } else {
+            warn!("Tone mapping is only used for Rgba16Float output");
+        }
+    }
+
+    /// Seek to the start (errors are logged, e.g. non-seekable source)
+    fn rewind(&mut self) {
+        let result = match (&mut self.hdr_decoder, &mut self.decoder) {
+            (Some(hdr_decoder), _) => hdr_decoder.seek_to_start().map_err(|e| e.to_string()),
+            (None, Some(decoder)) => decoder.seek_to_start().map_err(|e| e.to_string()),
+            (None, None) => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to rewind: {}", e);
+        }
+    }
+
+    fn decode_next_frame(&mut self) -> Option<Vec<u8>> {
+        if let Some(hdr_decoder) = &mut self.hdr_decoder {
+            hdr_decoder.decode_next()
+        } else {
+            let frame_or_not = self.decoder.as_mut()?.decode_raw().ok();
+            opt_bgra_to_bgra_u8(&frame_or_not)
+        }
+    }
+}
+
+fn bit_depth_from_pixel_format(pixel_format: ffmpeg::format::Pixel) -> Option<u32> {
+    // depth of the first component, e.g. 10 for YUV420P10LE
+    let descriptor = unsafe { ffmpeg::ffi::av_pix_fmt_desc_get(pixel_format.into()) };
+    if descriptor.is_null() {
+        return None;
+    }
+    let depth = unsafe { (*descriptor).comp[0].depth };
+    if depth > 0 { Some(depth as u32) } else { None }
+}
+
+fn display_matrix_from_bytes(data: &[u8]) -> Option<[i32; 9]> {
+    if data.len() < 36 {
+        return None;
+    }
+    let mut matrix = [0i32; 9];
+    for (i, value) in matrix.iter_mut().enumerate() {
+        *value = i32::from_ne_bytes([data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]]);
+    }
+    Some(matrix)
+}
+
+/// display matrix for `rotate` tag of old files
+fn display_matrix_from_degrees(degrees: f64) -> [i32; 9] {
+    // same as av_display_rotation_set()
+    let radians = -degrees.to_radians();
+    let c = (radians.cos() * 65536.0).round() as i32;
+    let s = (radians.sin() * 65536.0).round() as i32;
+    [c, -s, 0, s, c, 0, 0, 0, 1 << 30]
+}
+
+/// Read container and video stream metadata of a movie file.
+pub fn read_metadata(path: &Path) -> Result<MovieMetadata, ffmpeg::Error> {
+    let input = ffmpeg::format::input(&path)?;
+    let stream = input
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or(ffmpeg::Error::StreamNotFound)?;
+
+    let parameters = stream.parameters();
+    let codec = parameters.id().name().to_string();
+    let video = ffmpeg::codec::context::Context::from_parameters(parameters)?
+        .decoder()
+        .video()?;
+
+    let frame_rate = stream.avg_frame_rate();
+    let frame_rate = if frame_rate.denominator() != 0 {
+        Some(frame_rate.numerator() as f32 / frame_rate.denominator() as f32)
+    } else {
+        None
+    };
+
+    let aspect_ratio = video.aspect_ratio();
+    let pixel_aspect_ratio = if aspect_ratio.numerator() > 0 && aspect_ratio.denominator() > 0 {
+        Some((aspect_ratio.numerator(), aspect_ratio.denominator()))
+    } else {
+        Some((1, 1))
+    };
+
+    let mut tags: Vec<(String, String)> = input.metadata().iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    tags.extend(stream.metadata().iter().map(|(k, v)| (format!("stream:{}", k), v.to_string())));
+    let title = input.metadata().get("title").map(|s| s.to_string());
+
+    let display_matrix = stream.side_data()
+        .find(|side_data| side_data.kind() == ffmpeg::packet::side_data::Type::DisplayMatrix)
+        .and_then(|side_data| display_matrix_from_bytes(side_data.data()))
+        .or_else(|| stream.metadata().get("rotate")
+            .and_then(|degrees| degrees.parse::<f64>().ok())
+            // `rotate` is clockwise, the display matrix is counterclockwise
+            .map(|degrees| display_matrix_from_degrees(-degrees)));
+
+    let chapters = input.chapters()
+        .map(|chapter| {
+            let time_base: f64 = chapter.time_base().into();
+            MovieChapter {
+                title: chapter.metadata().get("title").map(|s| s.to_string()),
+                start: Duration::from_secs_f64((chapter.start() as f64 * time_base).max(0.0)),
+                end: Duration::from_secs_f64((chapter.end() as f64 * time_base).max(0.0)),
+            }
+        })
+        .collect();
+
+    let bitrate = if input.bit_rate() > 0 { Some(input.bit_rate() as u64) } else { None };
+
+    Ok(MovieMetadata {
+        codec: Some(codec),
+        container: Some(input.format().name().to_string()),
+        frame_rate,
+        bitrate,
+        bit_depth: bit_depth_from_pixel_format(video.format()),
+        color_primaries: Some(format!("{:?}", video.color_primaries())),
+        color_transfer: Some(format!("{:?}", video.color_transfer_characteristic())),
+        pixel_aspect_ratio,
+        display_matrix,
+        title,
+        tags,
+        chapters,
+    })
+}
+
+/// ASS event (`ReadOrder,Layer,Style,Name,MarginL,MarginR,MarginV,Effect,Text`) to plain text
+fn ass_to_plain_text(ass: &str) -> String {
+    let ass = ass.trim_end();
+    let text = if let Some(dialogue) = ass.strip_prefix("Dialogue:") {
+        // old style: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
+        dialogue.splitn(10, ',').nth(9).unwrap_or("")
+    } else {
+        ass.splitn(9, ',').nth(8).unwrap_or("")
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut in_override = false;
+    for c in text.chars() {
+        match c {
+            '{' => in_override = true,
+            '}' => in_override = false,
+            _ if in_override => {},
+            _ => result.push(c),
+        }
+    }
+    result.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", " ")
+}
+
+/// Decoder of an embedded subtitle stream, and where the stream ends (if the container tells)
+struct EmbeddedSubtitleDecoder {
+    index: usize,
+    time_base: f64,
+    decoder: ffmpeg::decoder::Subtitle,
+    track: SubtitleTrack,
+    packets: u64,
+    expected_packets: Option<u64>,
+    /// end of the last packet, and of the stream (in stream time base)
+    last_end_pts: i64,
+    end_pts: Option<i64>,
+}
+
+impl EmbeddedSubtitleDecoder {
+    fn is_ended(&self) -> bool {
+        self.expected_packets.is_some_and(|n| self.packets >= n)
+            || self.end_pts.is_some_and(|end| self.last_end_pts >= end)
+    }
+}
+
+/// Read all embedded text subtitle streams (bitmap subtitles are skipped).
+///
+/// NOTE: this demuxes until all subtitle streams end,
+/// which is the whole file if the container doesn't tell their packet count or duration.
+pub fn read_embedded_subtitles(path: &Path) -> Result<Vec<SubtitleTrack>, ffmpeg::Error> {
+    let mut input = ffmpeg::format::input(&path)?;
+
+    let mut decoders = vec![];
+    for stream in input.streams() {
+        if stream.parameters().medium() != ffmpeg::media::Type::Subtitle {
+            continue;
+        }
+        let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())?
+            .decoder()
+            .subtitle()?;
+        let track = SubtitleTrack {
+            language: stream.metadata().get("language").map(|s| s.to_string()),
+            cues: vec![],
+        };
+        decoders.push(EmbeddedSubtitleDecoder {
+            index: stream.index(),
+            time_base: stream.time_base().into(),
+            decoder,
+            track,
+            packets: 0,
+            expected_packets: (stream.frames() > 0).then_some(stream.frames() as u64),
+            last_end_pts: 0,
+            end_pts: (stream.duration() > 0).then(|| stream.start_time().max(0) + stream.duration()),
+        });
+    }
 
-    FFmpegMoviePlayer {
-        decoder,
-        decoder_frame_number: 0,
-        cached_frame: None,
-        state_controller: MoviePlayerStateController::default(),
-        blank_mode: BlankMode::default(),
+    if decoders.is_empty() {
+        return Ok(vec![]);
     }
+
+    for result in input.packets() {
+        if decoders.iter().all(|d| d.is_ended()) {
+            break;
+        }
+        let (stream, packet) = result?;
+        let Some(EmbeddedSubtitleDecoder { time_base, decoder, track, packets, last_end_pts, .. }) =
+            decoders.iter_mut().find(|d| d.index == stream.index()) else {
+            continue;
+        };
+        *packets += 1;
+        if let Some(pts) = packet.pts() {
+            *last_end_pts = (*last_end_pts).max(pts + packet.duration().max(0));
+        }
+
+        let mut subtitle = ffmpeg::Subtitle::new();
+        if !decoder.decode(&packet, &mut subtitle).unwrap_or(false) {
+            continue;
+        }
+
+        let text: Vec<String> = subtitle.rects()
+            .filter_map(|rect| match rect {
+                ffmpeg::subtitle::Rect::Text(text) => Some(text.get().to_string()),
+                ffmpeg::subtitle::Rect::Ass(ass) => Some(ass_to_plain_text(ass.get())),
+                _ => None,
+            })
+            .collect();
+        if text.is_empty() {
+            continue;
+        }
+
+        let packet_time = packet.pts().unwrap_or(0).max(0) as f64 * *time_base;
+        let start = packet_time + subtitle.start() as f64 / 1000.0;
+        let end = if subtitle.end() > subtitle.start() {
+            packet_time + subtitle.end() as f64 / 1000.0
+        } else {
+            packet_time + packet.duration().max(0) as f64 * *time_base
+        };
+
+        track.cues.push(SubtitleCue {
+            start: Duration::from_secs_f64(start),
+            end: Duration::from_secs_f64(end.max(start)),
+            text: text.join("\n"),
+        });
+    }
+
+    Ok(decoders.into_iter()
+        .map(|d| SubtitleTrack::new(d.track.cues).with_language(d.track.language))
+        .filter(|track| !track.cues.is_empty())
+        .collect())
+}
+
+/// Stream of a movie file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FFmpegStreamInfo {
+    pub index: usize,
+    /// `Video`, `Audio`, `Subtitle`, ...
+    pub media_type: String,
+    pub codec: String,
+    pub duration: Option<Duration>,
+    /// from container (`None` if unknown)
+    pub frame_count: Option<u64>,
+    pub language: Option<String>,
+}
+
+/// Read info of all streams of a movie file.
+pub fn read_stream_infos(path: &Path) -> Result<Vec<FFmpegStreamInfo>, ffmpeg::Error> {
+    let input = ffmpeg::format::input(&path)?;
+    Ok(input.streams()
+        .map(|stream| {
+            let parameters = stream.parameters();
+            let time_base: f64 = stream.time_base().into();
+            FFmpegStreamInfo {
+                index: stream.index(),
+                media_type: format!("{:?}", parameters.medium()),
+                codec: parameters.id().name().to_string(),
+                duration: (stream.duration() > 0).then(|| Duration::from_secs_f64(stream.duration() as f64 * time_base)),
+                frame_count: (stream.frames() > 0).then_some(stream.frames() as u64),
+                language: stream.metadata().get("language").map(|s| s.to_string()),
+            }
+        })
+        .collect())
+}
+
+/// Result of `validate_video_frames`
+#[derive(Debug, Clone, Default)]
+pub struct FrameValidation {
+    pub decoded_frames: u64,
+    /// errors with frame / packet number
+    pub errors: Vec<String>,
+}
+
+/// Decode all frames of the video stream, and collect decode errors and corrupt frames.
+///
+/// NOTE: this decodes the whole file.
+pub fn validate_video_frames(path: &Path) -> Result<FrameValidation, ffmpeg::Error> {
+    let mut input = ffmpeg::format::input(&path)?;
+    let stream = input
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or(ffmpeg::Error::StreamNotFound)?;
+    let stream_index = stream.index();
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())?
+        .decoder()
+        .video()?;
+
+    let mut validation = FrameValidation::default();
+    let mut frame = ffmpeg::frame::Video::empty();
+    let mut receive_frames = |decoder: &mut ffmpeg::decoder::Video, validation: &mut FrameValidation| {
+        while decoder.receive_frame(&mut frame).is_ok() {
+            if frame.is_corrupt() {
+                validation.errors.push(format!("frame {}: corrupt", validation.decoded_frames));
+            }
+            validation.decoded_frames += 1;
+        }
+    };
+
+    for (packet_number, result) in input.packets().enumerate() {
+        let (stream, packet) = match result {
+            Ok(packet) => packet,
+            Err(e) => {
+                // can't continue demuxing
+                validation.errors.push(format!("packet {}: failed to read: {}", packet_number, e));
+                break;
+            },
+        };
+        if stream.index() != stream_index {
+            continue;
+        }
+        if let Err(e) = decoder.send_packet(&packet) {
+            validation.errors.push(format!("packet {}: {}", packet_number, e));
+        }
+        receive_frames(&mut decoder, &mut validation);
+    }
+    if decoder.send_eof().is_ok() {
+        receive_frames(&mut decoder, &mut validation);
+    }
+    Ok(validation)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FFmpegMovieLoaderSettings {
+    /// load embedded text subtitles as labeled sub assets (demuxes until the subtitle streams end on load)
+    pub load_subtitles: bool,
+    /// output texture format (use `Rgba16Float` or `Rgb10a2Unorm` for HDR / 10-bit sources)
+    pub output_format: FrameOutputFormat,
+}
+
+#[derive(TypePath)]
+pub struct FFmpegMovieLoader {
+    extensions: Vec<&'static str>,
+    /// movies are opened as files under this directory (set by `MoviePlayerPlugin`)
+    asset_root: MovieAssetRoot,
 }
 
-#[derive(Default, TypePath)]
-pub struct FFmpegMovieLoader;
+impl Default for FFmpegMovieLoader {
+    fn default() -> Self {
+        Self::with_extensions(&default_ffmpeg_extensions())
+    }
+}
+
+impl FFmpegMovieLoader {
+    pub fn with_extensions(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            asset_root: MovieAssetRoot::default(),
+        }
+    }
+
+    pub fn with_asset_root(mut self, asset_root: MovieAssetRoot) -> Self {
+        self.asset_root = asset_root;
+        self
+    }
+}
 
 impl AssetLoader for FFmpegMovieLoader {
     type Asset = FFmpegMovie;
-    type Settings = ();
+    type Settings = FFmpegMovieLoaderSettings;
     type Error = std::io::Error;
   
     fn load(
         &self,
         _reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+      let load_subtitles = settings.load_subtitles;
+      let output_format = settings.output_format;
+      let asset_root = self.asset_root.clone();
       Box::pin(async move {
-        let asset_dir = "assets"; // FIXME: just WORKAROUND
-        let p = Path::new(asset_dir).join(load_context.path());
-        let asset_path = p.to_str().unwrap();
-        // TODO: error handling
-        let player = load_movie(asset_path);
+        let p = asset_root.resolve(load_context.path())?;
+        let mut player = try_load_movie(&p)
+          .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        player.set_output_format(output_format)
+          .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let mut subtitles = vec![];
+        if load_subtitles {
+          let tracks = read_embedded_subtitles(&p).unwrap_or_else(|e| {
+            warn!("Failed to read subtitles: {}", e);
+            vec![]
+          });
+          for (i, track) in tracks.into_iter().enumerate() {
+            subtitles.push(load_context.add_labeled_asset(format!("subtitle{}", i), track));
+          }
+        }
+
         Ok(FFmpegMovie {
           player,
+          subtitles,
         })
       })
     }
   
     fn extensions(&self) -> &[&str] {
-      &["mp4", "mov", "avi", "webm", "mkv", "ogv", "m4v"]
+      &self.extensions
     }
 }
 
@@ -133,8 +620,7 @@ impl MoviePlayer for FFmpegMoviePlayer {
     }
     
     fn get_duration(&self) -> Duration {
-        let time = self.decoder.duration().ok().unwrap();
-        Duration::from_secs_f64(time.as_secs_f64())
+        self.duration
     }
 
     fn set_volume(&mut self, _volume: f32) {
@@ -148,8 +634,11 @@ impl MoviePlayer for FFmpegMoviePlayer {
     }
     
     fn get_resolution(&self) -> (u32, u32) {
-        let size = self.decoder.size();
-        size
+        self.resolution
+    }
+
+    fn get_metadata(&self) -> MovieMetadata {
+        self.metadata.clone()
     }
 }
 
@@ -203,7 +692,7 @@ impl Blankable for FFmpegMoviePlayer {
 //     }
 // }
 
-fn opt_bgra_to_bgra_u8(frame_or_not: &Option<video_rs::ffmpeg::frame::Video>) -> Option<Vec<u8>> {
+pub(crate) fn opt_bgra_to_bgra_u8(frame_or_not: &Option<video_rs::ffmpeg::frame::Video>) -> Option<Vec<u8>> {
     if let Some(frame) = frame_or_not {
         let size: usize = (frame.width() *  frame.height() * 4) as usize;
         unsafe {
@@ -219,20 +708,27 @@ fn opt_bgra_to_bgra_u8(frame_or_not: &Option<video_rs::ffmpeg::frame::Video>) ->
 impl BGRAImageFrameProvider for FFmpegMoviePlayer {
     fn get_first_frame_bgra(&mut self) -> Option<Vec<u8>> {
         // seek to first frame
-        self.decoder.seek_to_start().unwrap();
+        self.rewind();
         // let frame_or_not = self.decoder.decode().ok();
         // opt_rgb_to_bgra_u8(frame_or_not)
-        let frame_or_not = self.decoder.decode_raw().ok();
-        opt_bgra_to_bgra_u8(&frame_or_not)
+        self.decode_next_frame()
     }
 
     fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>> {
         // seek to last frame
-        let frame_count: usize = (self.get_duration().as_secs_f64() * (self.decoder.frame_rate() as f64)).round() as usize;
-        self.decoder.seek_to_frame((frame_count as i64) - 1).unwrap();
+        let frame_count: usize = (self.get_duration().as_secs_f64() * (self.frame_rate as f64)).round() as usize;
+        if let Some(hdr_decoder) = &mut self.hdr_decoder {
+            let secs = (frame_count as f64 - 1.0).max(0.0) / (self.frame_rate as f64);
+            return hdr_decoder.seek_to_secs(secs).ok().flatten();
+        }
+        let decoder = self.decoder.as_mut()?;
+        if let Err(e) = decoder.seek_to_frame((frame_count as i64) - 1) {
+            warn!("Failed to seek to the last frame: {}", e);
+            return None;
+        }
         // let frame_or_not: Option<(video_rs::Time, ArrayBase<OwnedRepr<u8>, Dim<[usize; 3]>>)> = self.decoder.decode().ok();
         // opt_rgb_to_bgra_u8(frame_or_not)
-        let frame_or_not = self.decoder.decode_raw().ok();
+        let frame_or_not = decoder.decode_raw().ok();
         opt_bgra_to_bgra_u8(&frame_or_not)
     }
 
@@ -241,19 +737,19 @@ impl BGRAImageFrameProvider for FFmpegMoviePlayer {
         // self.cached_frame.clone()
 
         let position = self.get_position();
-        let frame_number: usize = ((position.as_secs_f64() * (self.decoder.frame_rate() as f64)).round()) as usize;
+        let frame_number: usize = ((position.as_secs_f64() * (self.frame_rate as f64)).round()) as usize;
 
         if frame_number < self.decoder_frame_number {
             // go to head
-            self.decoder.seek_to_start().unwrap();
+            self.rewind();
             self.decoder_frame_number = 0;
         }
 
         let mut frame_or_not = None;
         while self.decoder_frame_number < frame_number {
-            frame_or_not = self.decoder.decode_raw().ok();
+            frame_or_not = self.decode_next_frame();
             if frame_or_not.is_some() {
-                self.cached_frame = opt_bgra_to_bgra_u8(&frame_or_not);
+                self.cached_frame = frame_or_not.clone();
             }
             self.decoder_frame_number += 1;
         }
@@ -263,7 +759,7 @@ impl BGRAImageFrameProvider for FFmpegMoviePlayer {
         // println!("decoder_frame_number: {}", self.decoder_frame_number);
 
         if frame_or_not.is_some() {
-            opt_bgra_to_bgra_u8(&frame_or_not)
+            frame_or_not
         } else {
             self.cached_frame.clone()
         }
@@ -271,19 +767,19 @@ impl BGRAImageFrameProvider for FFmpegMoviePlayer {
 
     fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>> {
         let position = self.get_position();
-        let frame_number: usize = ((position.as_secs_f64() * (self.decoder.frame_rate() as f64)).round()) as usize;
+        let frame_number: usize = ((position.as_secs_f64() * (self.frame_rate as f64)).round()) as usize;
 
         if frame_number < self.decoder_frame_number {
             // go to head
-            self.decoder.seek_to_start().unwrap();
+            self.rewind();
             self.decoder_frame_number = 0;
         }
 
         let mut frame_or_not = None;
         while self.decoder_frame_number < frame_number {
-            frame_or_not = self.decoder.decode_raw().ok();
+            frame_or_not = self.decode_next_frame();
             if frame_or_not.is_some() {
-                self.cached_frame = opt_bgra_to_bgra_u8(&frame_or_not);
+                self.cached_frame = frame_or_not.clone();
             }
             self.decoder_frame_number += 1;
         }
@@ -293,11 +789,40 @@ impl BGRAImageFrameProvider for FFmpegMoviePlayer {
         // println!("decoder_frame_number: {}", self.decoder_frame_number);
 
         if frame_or_not.is_some() {
-            opt_bgra_to_bgra_u8(&frame_or_not)
+            frame_or_not
         } else {
             self.cached_frame.clone()
         }
     }
+
+    /// decoded by the separate grab decoder (seek to the keyframe before, then decode until the position)
+    fn get_grabbed_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        let secs = self.get_position().as_secs_f64();
+        let output_format = self.get_output_format();
+        if self.grab_decoder.is_none() {
+            let Some(path) = &self.path else {
+                warn!("Frames can be grabbed only from files");
+                return None;
+            };
+            self.grab_decoder = HdrDecoder::new(path, output_format)
+                .map_err(|e| warn!("Failed to open grab decoder: {}", e))
+                .ok();
+        }
+        let grab_decoder = self.grab_decoder.as_mut()?;
+        grab_decoder.output_format = output_format;
+        if let Some(hdr_decoder) = &self.hdr_decoder {
+            grab_decoder.tone_mapping = hdr_decoder.tone_mapping.clone();
+        }
+        grab_decoder.seek_to_secs(secs).ok().flatten()
+    }
+
+    fn get_frame_texture_format(&self) -> TextureFormat {
+        self.get_output_format().get_texture_format()
+    }
+
+    fn get_blank_pixel(&self, bgra: &[u8]) -> Vec<u8> {
+        self.get_output_format().pixel_from_bgra8(bgra)
+    }
 }
 
 // test
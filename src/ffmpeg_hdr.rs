@@ -0,0 +1,222 @@
+use bevy::platform::cell::SyncCell;
+use video_rs::ffmpeg;
+use video_rs::ffmpeg::format::Pixel;
+use video_rs::ffmpeg::software::scaling;
+
+use crate::hdr::rgba64_to_rgb10a2;
+use crate::hdr::rgba64_to_rgba16_float;
+use crate::hdr::FrameOutputFormat;
+use crate::hdr::HdrTransfer;
+use crate::hdr::ToneMapping;
+
+use std::path::Path;
+
+/// Decoder for high bit depth output (`Rgba16Float` / `Rgb10a2Unorm`).
+///
+/// video-rs `Decoder` always converts frames into 8-bit BGRA,
+/// so this decodes with ffmpeg directly and converts via RGBA64.
+///
+/// NOTE: ffmpeg contexts are wrapped in `SyncCell` (only reachable through `&mut self`), so this is `Sync` without unsafe impl.
+pub struct HdrDecoder {
+    input: SyncCell<ffmpeg::format::context::Input>,
+    stream_index: usize,
+    decoder: SyncCell<ffmpeg::decoder::Video>,
+    scaler: SyncCell<Scaler>,
+    transfer: HdrTransfer,
+    time_base: f64,
+    eof: bool,
+    pub output_format: FrameOutputFormat,
+    pub tone_mapping: ToneMapping,
+}
+
+/// swscale context, which ffmpeg bindings don't mark as `Send`
+struct Scaler(scaling::Context);
+
+// SAFETY: `Scaler` is only used by one thread at a time:
+// - it is private to `HdrDecoder` and only stored in `SyncCell`, whose `get` needs `&mut self`,
+//   so the only access (`convert`) is through an exclusive borrow of the decoder,
+// - it is never cloned and no reference to the inner `SwsContext` escapes,
+// - `Scaler` is not `Sync`, so sharing `&Scaler` across threads does not compile.
+// Moving it between threads (e.g. the player in a component) is fine, because swscale keeps
+// no thread-local state or thread-bound handles in the context, only its own heap allocation.
+unsafe impl Send for Scaler {}
+
+fn transfer_from_ffmpeg(transfer: ffmpeg::color::TransferCharacteristic) -> HdrTransfer {
+    match transfer {
+        ffmpeg::color::TransferCharacteristic::SMPTE2084 => HdrTransfer::Pq,
+        ffmpeg::color::TransferCharacteristic::ARIB_STD_B67 => HdrTransfer::Hlg,
+        ffmpeg::color::TransferCharacteristic::Linear => HdrTransfer::Linear,
+        _ => HdrTransfer::Sdr,
+    }
+}
+
+impl HdrDecoder {
+    pub fn new(path: &Path, output_format: FrameOutputFormat) -> Result<Self, ffmpeg::Error> {
+        let input = ffmpeg::format::input(&path)?;
+        let (stream_index, time_base, parameters) = {
+            let stream = input
+                .streams()
+                .best(ffmpeg::media::Type::Video)
+                .ok_or(ffmpeg::Error::StreamNotFound)?;
+            let time_base: f64 = stream.time_base().into();
+            (stream.index(), time_base, stream.parameters())
+        };
+        let decoder = ffmpeg::codec::context::Context::from_parameters(parameters)?
+            .decoder()
+            .video()?;
+
+        let scaler = Scaler(scaling::Context::get(
+            decoder.format(),
+            decoder.width(),
+            decoder.height(),
+            Pixel::RGBA64LE,
+            decoder.width(),
+            decoder.height(),
+            scaling::Flags::BILINEAR,
+        )?);
+        let transfer = transfer_from_ffmpeg(decoder.color_transfer_characteristic());
+
+        Ok(Self {
+            input: SyncCell::new(input),
+            stream_index,
+            decoder: SyncCell::new(decoder),
+            scaler: SyncCell::new(scaler),
+            transfer,
+            time_base,
+            eof: false,
+            output_format,
+            tone_mapping: ToneMapping::default(),
+        })
+    }
+
+    pub fn get_transfer(&self) -> HdrTransfer {
+        self.transfer
+    }
+
+    pub fn seek_to_start(&mut self) -> Result<(), ffmpeg::Error> {
+        self.input.get().seek(0, ..0)?;
+        self.decoder.get().flush();
+        self.eof = false;
+        Ok(())
+    }
+
+    /// Seek to the keyframe before the time, then decode until the time
+    pub fn seek_to_secs(&mut self, secs: f64) -> Result<Option<Vec<u8>>, ffmpeg::Error> {
+        let timestamp = (secs * ffmpeg::ffi::AV_TIME_BASE as f64) as i64;
+        self.input.get().seek(timestamp, ..timestamp)?;
+        self.decoder.get().flush();
+        self.eof = false;
+
+        let mut last = None;
+        while let Some((pts_secs, frame)) = self.decode_next_with_time() {
+            last = Some(frame);
+            if pts_secs >= secs {
+                break;
+            }
+        }
+        Ok(last)
+    }
+
+    /// Decode next frame into bytes of `output_format`
+    pub fn decode_next(&mut self) -> Option<Vec<u8>> {
+        self.decode_next_with_time().map(|(_, frame)| frame)
+    }
+
+    fn decode_next_with_time(&mut self) -> Option<(f64, Vec<u8>)> {
+        loop {
+            let mut decoded = ffmpeg::frame::Video::empty();
+            if self.decoder.get().receive_frame(&mut decoded).is_ok() {
+                let pts_secs = decoded.pts().unwrap_or(0) as f64 * self.time_base;
+                return self.convert(&decoded).map(|frame| (pts_secs, frame));
+            }
+            if self.eof {
+                return None;
+            }
+
+            let packet = match self.input.get().packets().next() {
+                Some(Ok((stream, packet))) => Some((stream.index(), packet)),
+                _ => None,
+            };
+            match packet {
+                Some((stream_index, packet)) => {
+                    if stream_index == self.stream_index {
+                        let _ = self.decoder.get().send_packet(&packet);
+                    }
+                },
+                None => {
+                    let _ = self.decoder.get().send_eof();
+                    self.eof = true;
+                },
+            }
+        }
+    }
+
+    fn convert(&mut self, decoded: &ffmpeg::frame::Video) -> Option<Vec<u8>> {
+        let mut rgba64 = ffmpeg::frame::Video::empty();
+        self.scaler.get().0.run(decoded, &mut rgba64).ok()?;
+
+        // copy rows into tightly packed u16 (stride may have padding)
+        let width = rgba64.width() as usize;
+        let height = rgba64.height() as usize;
+        let stride = rgba64.stride(0);
+        let data = rgba64.data(0);
+        let mut pixels: Vec<u16> = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            let row = &data[y * stride..y * stride + width * 8];
+            pixels.extend(row.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])));
+        }
+
+        match self.output_format {
+            FrameOutputFormat::Rgba16Float => Some(rgba64_to_rgba16_float(&pixels, self.transfer, &self.tone_mapping)),
+            FrameOutputFormat::Rgb10a2Unorm => Some(rgba64_to_rgb10a2(&pixels)),
+            FrameOutputFormat::Bgra8 => Some(pixels.chunks_exact(4)
+                .flat_map(|px| [px[2], px[1], px[0], px[3]].map(|c| (c >> 8) as u8))
+                .collect()),
+        }
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::process::Stdio;
+
+    /// 10-bit 64x48 fixture made by the ffmpeg command (`None` if not available)
+    fn create_10bit_movie() -> Option<std::path::PathBuf> {
+        let path = std::env::temp_dir().join(format!("bevy_movie_player_10bit_{}.mkv", std::process::id()));
+        let status = Command::new("ffmpeg")
+            .args(["-loglevel", "quiet", "-y",
+                "-f", "lavfi", "-i", "testsrc=size=64x48:rate=10", "-t", "1",
+                "-pix_fmt", "yuv420p10le", "-c:v", "ffv1"])
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .ok()?;
+        status.success().then_some(path)
+    }
+
+    #[test]
+    fn decode_10bit() {
+        let Some(path) = create_10bit_movie() else {
+            eprintln!("ffmpeg command not found, skipped");
+            return;
+        };
+        assert_eq!(crate::ffmpeg::read_metadata(&path).unwrap().bit_depth, Some(10));
+
+        let mut decoder = HdrDecoder::new(&path, FrameOutputFormat::Rgba16Float).unwrap();
+        assert_eq!(decoder.get_transfer(), HdrTransfer::Sdr);
+        assert_eq!(decoder.decode_next().map(|frame| frame.len()), Some(64 * 48 * 8));
+
+        decoder.output_format = FrameOutputFormat::Rgb10a2Unorm;
+        decoder.seek_to_start().unwrap();
+        assert_eq!(decoder.decode_next().map(|frame| frame.len()), Some(64 * 48 * 4));
+
+        // 10 frames, then end
+        assert_eq!(std::iter::from_fn(|| decoder.decode_next()).count(), 9);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,520 @@
+use bevy::asset::io::AssetReader;
+use bevy::asset::io::AssetReaderError;
+use bevy::asset::io::AssetSourceBuilder;
+use bevy::asset::io::PathStream;
+use bevy::asset::io::Reader;
+use bevy::asset::io::VecReader;
+use bevy::asset::AssetLoader;
+use bevy::asset::LoadContext;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+use derivative::Derivative;
+use serde::Deserialize;
+use serde::Serialize;
+use video_rs::Decoder;
+use video_rs::DecoderBuilder;
+use video_rs::Options;
+use video_rs::Url;
+
+use crate::blankable_image_data_provider::BGRAImageFrameProvider;
+use crate::blankable_image_data_provider::BlankMode;
+use crate::blankable_image_data_provider::Blankable;
+use crate::ffmpeg::opt_bgra_to_bgra_u8;
+use crate::movie_player::MovieMetadata;
+use crate::movie_player::MoviePlayerStateController;
+use crate::movie_player::MoviePlayer;
+use crate::movie_player::SeekOutOfBoundsError;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Asset source id for URL-backed movies.
+///
+/// `stream://udp/127.0.0.1:5000` is opened as `udp://127.0.0.1:5000`.
+pub const STREAM_ASSET_SOURCE: &str = "stream";
+
+/// Registers the `stream://` asset source.
+///
+/// NOTE: asset sources must be registered before `AssetPlugin` (i.e. `DefaultPlugins`).
+pub struct StreamAssetSourcePlugin;
+
+impl Plugin for StreamAssetSourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            STREAM_ASSET_SOURCE,
+            AssetSourceBuilder::new(|| Box::new(StreamAssetReader)),
+        );
+    }
+}
+
+/// Asset reader which returns the URL itself as asset bytes.
+/// The first path component is used as the URL scheme.
+pub struct StreamAssetReader;
+
+/// Convert `rtsp/host:8554/live` into `rtsp://host:8554/live`
+pub fn url_from_stream_path(path: &Path) -> Option<String> {
+    let path_str = path.to_str()?.replace('\\', "/");
+    let (scheme, rest) = path_str.split_once('/')?;
+    if scheme.is_empty() || rest.is_empty() {
+        return None;
+    }
+    Some(format!("{}://{}", scheme, rest))
+}
+
+impl AssetReader for StreamAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        url_from_stream_path(path)
+            .map(|url| VecReader::new(url.into_bytes()))
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        Err::<VecReader, _>(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn read_directory<'a>(&'a self, path: &'a Path) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FFmpegStreamSettings {
+    /// reopen the stream when it is dropped
+    pub reconnect: bool,
+    /// first wait before reconnecting (doubled on each failure)
+    pub reconnect_delay: Duration,
+    /// upper bound of the reconnect wait
+    pub max_reconnect_delay: Duration,
+    /// give up (and fail the load) when the stream can't be opened within this time. `None` waits forever
+    pub open_timeout: Option<Duration>,
+    /// a blocked read fails after this, so the stream is reconnected (or the worker stops after drop)
+    pub read_timeout: Duration,
+}
+
+impl Default for FFmpegStreamSettings {
+    fn default() -> Self {
+        Self {
+            reconnect: true,
+            reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(10),
+            open_timeout: Some(Duration::from_secs(30)),
+            read_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamStatus {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Closed(String),
+}
+
+struct StreamShared {
+    status: StreamStatus,
+    resolution: Option<(u32, u32)>,
+    frame_rate: f32,
+    latest_frame: Option<Vec<u8>>,
+    /// woken on first open (or failure)
+    open_waker: Option<Waker>,
+}
+
+/// Live movie player for network streams (rtsp/rtp/udp/http/file).
+///
+/// Frames are decoded on a worker thread and the latest one is shown.
+/// Live streams have unknown duration, can't seek, and ignore `LoopMode`.
+#[derive(Derivative, TypePath)]
+#[derivative(Debug)]
+pub struct FFmpegStreamPlayer {
+    pub url: String,
+    #[derivative(Debug="ignore")]
+    shared: Arc<Mutex<StreamShared>>,
+    #[derivative(Debug="ignore")]
+    stop_flag: Arc<AtomicBool>,
+    #[derivative(Debug="ignore")]
+    state_controller: MoviePlayerStateController,
+    blank_mode: BlankMode,
+}
+
+#[derive(Asset, TypePath, Derivative)]
+#[derivative(Debug)]
+pub struct FFmpegStream {
+    #[derivative(Debug="ignore")]
+    pub player: FFmpegStreamPlayer,
+}
+
+impl From<FFmpegStreamPlayer> for FFmpegStream {
+    fn from(player: FFmpegStreamPlayer) -> Self {
+        FFmpegStream {
+            player,
+        }
+    }
+}
+
+impl Drop for FFmpegStreamPlayer {
+    fn drop(&mut self) {
+        // worker exits after current decode returns (at most `read_timeout`)
+        self.stop_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+fn close_stream(shared: &Mutex<StreamShared>, reason: String) {
+    let mut shared = shared.lock().unwrap();
+    shared.status = StreamStatus::Closed(reason);
+    if let Some(waker) = shared.open_waker.take() {
+        waker.wake();
+    }
+}
+
+fn open_decoder(url: &str, read_timeout: Duration) -> Result<Decoder, String> {
+    let source = url.parse::<Url>().map_err(|e| e.to_string())?;
+    // `rw_timeout` (microseconds) interrupts blocking protocol reads
+    let options: Options = HashMap::from([
+        ("rw_timeout".to_string(), read_timeout.as_micros().to_string()),
+    ]).into();
+    DecoderBuilder::new(source)
+        .with_options(&options)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn run_stream_worker(url: String, settings: FFmpegStreamSettings, shared: Arc<Mutex<StreamShared>>, stop_flag: Arc<AtomicBool>) {
+    let mut reconnect_delay = settings.reconnect_delay;
+    let started = Instant::now();
+    let mut opened = false;
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        match open_decoder(&url, settings.read_timeout) {
+            Ok(mut decoder) => {
+                opened = true;
+                reconnect_delay = settings.reconnect_delay;
+                {
+                    let mut shared = shared.lock().unwrap();
+                    shared.status = StreamStatus::Connected;
+                    shared.resolution = Some(decoder.size());
+                    shared.frame_rate = decoder.frame_rate();
+                    if let Some(waker) = shared.open_waker.take() {
+                        waker.wake();
+                    }
+                }
+
+                while !stop_flag.load(Ordering::Relaxed) {
+                    match decoder.decode_raw() {
+                        Ok(frame) => {
+                            let frame = opt_bgra_to_bgra_u8(&Some(frame));
+                            shared.lock().unwrap().latest_frame = frame;
+                        },
+                        Err(e) => {
+                            warn!("Stream dropped: {} ({})", url, e);
+                            break;
+                        },
+                    }
+                }
+            },
+            Err(e) => {
+                warn!("Failed to open stream: {} ({})", url, e);
+                if !settings.reconnect {
+                    close_stream(&shared, e);
+                    return;
+                }
+                let timed_out = settings.open_timeout.is_some_and(|timeout| started.elapsed() >= timeout);
+                if !opened && timed_out {
+                    close_stream(&shared, format!("open timed out: {}", e));
+                    return;
+                }
+            },
+        }
+
+        if !settings.reconnect || stop_flag.load(Ordering::Relaxed) {
+            shared.lock().unwrap().status = StreamStatus::Closed("stream ended".to_string());
+            return;
+        }
+
+        shared.lock().unwrap().status = StreamStatus::Reconnecting;
+        std::thread::sleep(reconnect_delay);
+        reconnect_delay = (reconnect_delay * 2).min(settings.max_reconnect_delay);
+    }
+}
+
+/// Waits until the stream is opened once (or failed without reconnect)
+struct StreamOpenFuture {
+    shared: Arc<Mutex<StreamShared>>,
+}
+
+impl Future for StreamOpenFuture {
+    type Output = Result<(), std::io::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.resolution.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        if let StreamStatus::Closed(e) = &shared.status {
+            return Poll::Ready(Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, e.clone())));
+        }
+        shared.open_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Open a network stream without blocking the calling thread.
+///
+/// Resolves after the stream is opened once. With `reconnect` enabled, this keeps waiting until the source appears,
+/// up to `open_timeout`.
+pub async fn open_stream(url: &str, settings: FFmpegStreamSettings) -> Result<FFmpegStreamPlayer, std::io::Error> {
+    let shared = Arc::new(Mutex::new(StreamShared {
+        status: StreamStatus::Connecting,
+        resolution: None,
+        frame_rate: 0.0,
+        latest_frame: None,
+        open_waker: None,
+    }));
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    let player = FFmpegStreamPlayer {
+        url: url.to_string(),
+        shared: shared.clone(),
+        stop_flag: stop_flag.clone(),
+        state_controller: MoviePlayerStateController::default(),
+        blank_mode: BlankMode::default(),
+    };
+
+    let worker_url = url.to_string();
+    let worker_shared = shared.clone();
+    std::thread::Builder::new()
+        .name(format!("ffmpeg stream: {}", url))
+        .spawn(move || run_stream_worker(worker_url, settings, worker_shared, stop_flag))?;
+
+    StreamOpenFuture { shared }.await?;
+    Ok(player)
+}
+
+impl FFmpegStreamPlayer {
+    pub fn get_status(&self) -> StreamStatus {
+        self.shared.lock().unwrap().status.clone()
+    }
+
+    pub fn get_frame_rate(&self) -> f32 {
+        self.shared.lock().unwrap().frame_rate
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct FFmpegStreamLoader;
+
+impl AssetLoader for FFmpegStreamLoader {
+    type Asset = FFmpegStream;
+    type Settings = FFmpegStreamSettings;
+    type Error = std::io::Error;
+
+    fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        _load_context: &mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        let settings = settings.clone();
+        Box::pin(async move {
+            // bytes are URL (from `stream://` source, or `.stream` text file)
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let url = String::from_utf8(bytes)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            let player = open_stream(url.trim(), settings).await?;
+            Ok(player.into())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stream"]
+    }
+}
+
+impl MoviePlayer for FFmpegStreamPlayer {
+    fn get_state_controller(&self) -> &MoviePlayerStateController {
+        &self.state_controller
+    }
+
+    fn get_state_controller_mut(&mut self) -> &mut MoviePlayerStateController {
+        &mut self.state_controller
+    }
+
+    /// unknown for live streams
+    fn get_duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn get_resolution(&self) -> (u32, u32) {
+        self.shared.lock().unwrap().resolution.unwrap_or((1, 1))
+    }
+
+    fn get_metadata(&self) -> MovieMetadata {
+        let scheme = self.url.split_once("://").map(|(scheme, _)| scheme.to_string());
+        let frame_rate = self.get_frame_rate();
+        MovieMetadata {
+            container: scheme,
+            frame_rate: if frame_rate > 0.0 { Some(frame_rate) } else { None },
+            ..Default::default()
+        }
+    }
+
+    fn set_volume(&mut self, _volume: f32) {
+        warn!("Volume is not supported");
+        // do nothing
+    }
+
+    fn get_volume(&self) -> f32 {
+        warn!("Volume is not supported");
+        0.0
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    fn seek(&mut self, _to_time: Duration) -> Result<Duration, SeekOutOfBoundsError> {
+        warn!("Seek is not supported for live streams");
+        Err(SeekOutOfBoundsError {
+            actual_seeked_position: self.get_position(),
+        })
+    }
+
+    fn update(&mut self, bevy_elapsed_time: Duration) {
+        // NOTE: no end, so LoopMode is ignored
+        self.get_state_controller_mut().bevy_elapsed_time = bevy_elapsed_time;
+    }
+}
+
+impl Blankable for FFmpegStreamPlayer {
+    fn set_blank_mode(&mut self, blank_mode: BlankMode) {
+        self.blank_mode = blank_mode;
+    }
+
+    fn get_blank_mode(&self) -> BlankMode {
+        self.blank_mode
+    }
+}
+
+impl BGRAImageFrameProvider for FFmpegStreamPlayer {
+    fn get_first_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.shared.lock().unwrap().latest_frame.clone()
+    }
+
+    fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.shared.lock().unwrap().latest_frame.clone()
+    }
+
+    fn get_paused_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.shared.lock().unwrap().latest_frame.clone()
+    }
+
+    fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.shared.lock().unwrap().latest_frame.clone()
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::net::TcpListener;
+    use std::net::UdpSocket;
+    use std::process::Stdio;
+
+    #[test]
+    fn stream_path_to_url() {
+        assert_eq!(url_from_stream_path(Path::new("udp/127.0.0.1:5000")), Some("udp://127.0.0.1:5000".to_string()));
+        assert_eq!(url_from_stream_path(Path::new("rtsp/host:8554/live/cam1")), Some("rtsp://host:8554/live/cam1".to_string()));
+        assert_eq!(url_from_stream_path(Path::new("udp")), None);
+    }
+
+    #[test]
+    fn play_local_udp_stream() {
+        let settings = FFmpegStreamSettings {
+            reconnect: false,
+            ..default()
+        };
+
+        // stand-in live source: ffmpeg test pattern over loopback UDP.
+        // the port is free when picked, but can be taken before the stream binds it, so retry with another one
+        let mut opened = None;
+        for _ in 0..3 {
+            let port = UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+            let url = format!("udp://127.0.0.1:{}", port);
+            let server = Command::new("ffmpeg")
+                .args(["-loglevel", "quiet", "-re",
+                    "-f", "lavfi", "-i", "testsrc=size=64x48:rate=10",
+                    "-t", "20", "-f", "mpegts", &url])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            let Ok(mut server) = server else {
+                eprintln!("ffmpeg command not found, skipped");
+                return;
+            };
+            match bevy::tasks::block_on(open_stream(&url, settings.clone())) {
+                Ok(movie) => {
+                    opened = Some((movie, server));
+                    break;
+                },
+                Err(e) => {
+                    eprintln!("failed to open {}: {}, retrying", url, e);
+                    let _ = server.kill();
+                },
+            }
+        }
+        let (mut movie, mut server) = opened.expect("failed to open the local stream");
+
+        assert!(movie.is_live());
+        assert_eq!(movie.get_resolution(), (64, 48));
+        assert!(movie.seek(Duration::from_secs(1)).is_err());
+
+        movie.set_loop_mode(crate::movie_player::LoopMode::Stop);
+        movie.play();
+        movie.update(Duration::from_secs(100));
+        assert_eq!(movie.get_state(), crate::movie_player::PlayingState::Playing);
+
+        let started = Instant::now();
+        let mut frame = None;
+        while frame.is_none() && started.elapsed() < Duration::from_secs(10) {
+            frame = movie.get_playing_frame_bgra();
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(frame.map(|f| f.len()), Some(64 * 48 * 4));
+
+        let _ = server.kill();
+    }
+
+    #[test]
+    fn open_timeout() {
+        // the listener never sends anything, so every open blocks until the read timeout
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let settings = FFmpegStreamSettings {
+            reconnect_delay: Duration::from_millis(10),
+            open_timeout: Some(Duration::from_millis(100)),
+            read_timeout: Duration::from_millis(200),
+            ..default()
+        };
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let result = bevy::tasks::block_on(open_stream(&url, settings));
+        assert!(result.is_err());
+        drop(listener);
+    }
+}
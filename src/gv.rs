@@ -8,115 +8,247 @@ use derivative::Derivative;
 use gv_video::get_bgra_vec_from_frame;
 use gv_video::GVVideo;
 use gv_video::GVFormat;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::blankable_image_data_provider::BGRAImageFrameProvider;
 use crate::blankable_image_data_provider::BlankMode;
 use crate::blankable_image_data_provider::Blankable;
 use crate::blankable_image_data_provider::CompressedImageFrameProvider;
+use crate::gv_asset_stream::GVAssetStream;
+use crate::gv_asset_stream::DEFAULT_PREFETCH_FRAMES;
+use crate::gv_header::GVHeader;
+use crate::movie_format::default_gv_extensions;
+use crate::movie_format::MovieAssetRoot;
+use crate::movie_player::LoadMode;
+use crate::movie_player::MovieMetadata;
 use crate::movie_player::MoviePlayerStateController;
 use crate::movie_player::MoviePlayer;
+use crate::movie_player::PlayingState;
 
 use std::fs::File;
 use std::io::BufReader;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::path::Path;
 use std::time::Duration;
 use bevy::prelude::TypePath;
 
+pub(crate) use crate::gv_header::HEADER_SIZE;
+/// Size of each (address, size) entry of the frame table at the end of GV file
+pub(crate) const ADDRESS_SIZE_BLOCK_SIZE: u64 = 16;
+
 pub struct GVMoviePlayer<Reader: Read + Seek> {
     pub gv: GVVideo<Reader>,
     blank_mode: BlankMode,
     // blankable_controller: BlankableController,
     state_controller: MoviePlayerStateController,
+    /// last good frames, held while frames can't be read
+    held_frame_compressed: Option<Vec<u8>>,
+    held_frame_bgra: Option<Vec<u8>>,
+    /// (frame, error) not taken by `take_decode_errors` yet
+    decode_errors: Vec<(u32, String)>,
+    last_error_frame: Option<u32>,
+    /// shares frames with the reader, to check if a frame is read yet
+    asset_stream: Option<GVAssetStream>,
+    /// start of the frame table, frames must end before it
+    frame_data_end: u64,
+}
+
+/// Sent when a GV frame can't be read or decoded while playing (the last good frame is held)
+#[derive(Message, Debug, Clone)]
+pub struct FrameDecodeError {
+    pub movie: AssetId<GVMovie>,
+    pub frame: u32,
+    pub error: String,
 }
 
 #[derive(Asset, TypePath, Derivative)]
 #[derivative(Debug)]
 pub struct GVMovie {
     #[derivative(Debug="ignore")]
-    pub player: GVMoviePlayer<BufReader<File>>,
+    pub player: GVMoviePlayer<GVReader>,
 }
 
-#[derive(Asset, TypePath, Derivative)]
-#[derivative(Debug)]
-pub struct GVMovieOnMemory {
-    #[derivative(Debug="ignore")]
-    pub player: GVMoviePlayer<Cursor<Vec<u8>>>,
+/// Reader of `GVMovie`, selected by `GVLoaderSettings::load_mode`
+pub enum GVReader {
+    /// seek + read per frame
+    DiskStream(BufReader<File>),
+    /// seek + read per frame through bevy `AssetReader`, on the IO task pool
+    AssetStream(GVAssetStream),
+    /// all data on memory
+    OnMemory(Cursor<Vec<u8>>),
+    /// memory-mapped file
+    #[cfg(feature = "gv-mmap")]
+    MemoryMapped(Cursor<memmap2::Mmap>),
 }
 
-impl From<GVMoviePlayer<BufReader<File>>> for GVMovie {
-    fn from(player: GVMoviePlayer<BufReader<File>>) -> Self {
-        GVMovie {
-            player,
+impl GVReader {
+    /// Open a file with `load_mode`. `MemoryMapped` falls back to `DiskStream` without `gv-mmap` feature
+    pub fn open<P: AsRef<Path>>(path: P, load_mode: LoadMode) -> std::io::Result<Self> {
+        match load_mode {
+            LoadMode::OnMemory => Ok(GVReader::OnMemory(Cursor::new(std::fs::read(path)?))),
+            LoadMode::DiskStream => Ok(GVReader::DiskStream(BufReader::new(File::open(path)?))),
+            LoadMode::MemoryMapped => {
+                let file = File::open(path)?;
+                #[cfg(feature = "gv-mmap")]
+                {
+                    // SAFETY: the file must not be modified (truncated) while mapped, same as other mmap users
+                    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+                    Ok(GVReader::MemoryMapped(Cursor::new(mmap)))
+                }
+                #[cfg(not(feature = "gv-mmap"))]
+                {
+                    warn!("memory mapped GV needs `gv-mmap` feature, using disk stream instead");
+                    Ok(GVReader::DiskStream(BufReader::new(file)))
+                }
+            },
         }
     }
 }
 
-impl From<GVMoviePlayer<Cursor<Vec<u8>>>> for GVMovieOnMemory {
-    fn from(player: GVMoviePlayer<Cursor<Vec<u8>>>) -> Self {
-        GVMovieOnMemory {
+impl Read for GVReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            GVReader::DiskStream(reader) => reader.read(buf),
+            GVReader::AssetStream(reader) => reader.read(buf),
+            GVReader::OnMemory(reader) => reader.read(buf),
+            #[cfg(feature = "gv-mmap")]
+            GVReader::MemoryMapped(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for GVReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            GVReader::DiskStream(reader) => reader.seek(pos),
+            GVReader::AssetStream(reader) => reader.seek(pos),
+            GVReader::OnMemory(reader) => reader.seek(pos),
+            #[cfg(feature = "gv-mmap")]
+            GVReader::MemoryMapped(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// Settings of `GVMovieLoader`, e.g.
+/// `asset_server.load_with_settings("test.gv", |s: &mut GVLoaderSettings| s.load_mode = LoadMode::OnMemory)`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GVLoaderSettings {
+    /// `DiskStream` (default): small memory usage, reads frames through the asset source in background (with prefetch).
+    /// `OnMemory`: reads the whole file upfront (works with any asset source).
+    /// `MemoryMapped` (`--features gv-mmap`): on memory random access speed, without reading the whole file upfront.
+    pub load_mode: LoadMode,
+}
+
+impl From<GVMoviePlayer<GVReader>> for GVMovie {
+    fn from(player: GVMoviePlayer<GVReader>) -> Self {
+        GVMovie {
             player,
         }
     }
 }
 
-#[derive(Default, TypePath)]
-pub struct GVMovieLoader;
+#[derive(TypePath)]
+pub struct GVMovieLoader {
+    extensions: Vec<&'static str>,
+    /// for disk stream through `AssetReader` (set by `MoviePlayerPlugin`)
+    asset_server: Option<AssetServer>,
+    /// files of memory-mapped GV, and disk stream without `asset_server`
+    asset_root: MovieAssetRoot,
+}
 
-#[derive(Default, TypePath)]
-pub struct GVMovieOnMemoryLoader;
+impl Default for GVMovieLoader {
+    fn default() -> Self {
+        Self::with_extensions(&default_gv_extensions())
+    }
+}
+
+impl GVMovieLoader {
+    pub fn with_extensions(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            asset_server: None,
+            asset_root: MovieAssetRoot::default(),
+        }
+    }
+
+    /// Stream frames through `AssetReader` of the server, instead of reading files under `MovieAssetRoot` directly
+    pub fn with_asset_server(mut self, asset_server: AssetServer) -> Self {
+        self.asset_server = Some(asset_server);
+        self
+    }
+
+    pub fn with_asset_root(mut self, asset_root: MovieAssetRoot) -> Self {
+        self.asset_root = asset_root;
+        self
+    }
+}
 
 impl AssetLoader for GVMovieLoader {
     type Asset = GVMovie;
-    type Settings = ();
+    type Settings = GVLoaderSettings;
     type Error = std::io::Error;
   
     fn load(
         &self,
-        _reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
         load_context: &mut LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        let load_mode = settings.load_mode;
+        let asset_server = self.asset_server.clone();
+        let asset_root = self.asset_root.clone();
         Box::pin(async move {
-            let asset_dir = "assets"; // FIXME: just WORKAROUND
-            let asset_path_str = load_context.path().to_string();
-            let p = Path::new(asset_dir).join(asset_path_str);
-            let asset_path = p.to_str().unwrap();
-            // TODO: error handling
-            let player = load_gv(asset_path);
+            let seekable = reader.seekable().is_ok();
+            let reader = match (load_mode, asset_server) {
+                (LoadMode::DiskStream, Some(asset_server)) if seekable => {
+                    let path = load_context.path().clone();
+                    let stream = GVAssetStream::load(reader, asset_server, path, DEFAULT_PREFETCH_FRAMES).await?;
+                    return Ok(load_gv_from_asset_stream(stream)?.into());
+                },
+                (LoadMode::OnMemory, _) | (LoadMode::DiskStream, Some(_)) => {
+                    if load_mode == LoadMode::DiskStream {
+                        warn!("asset reader of {} is not seekable, loading GV on memory", load_context.path());
+                    }
+                    let mut bytes = Vec::new();
+                    reader.read_to_end(&mut bytes).await?;
+                    GVReader::OnMemory(Cursor::new(bytes))
+                },
+                _ => {
+                    let p = asset_root.resolve(load_context.path())?;
+                    GVReader::open(&p, load_mode)?
+                },
+            };
+            let player = try_load_gv_from_reader(reader)?;
             Ok(player.into())
         })
     }
   
     fn extensions(&self) -> &[&str] {
-      &["gv"]
+      &self.extensions
     }
 }
 
-impl AssetLoader for GVMovieOnMemoryLoader {
-    type Asset = GVMovieOnMemory;
-    type Settings = ();
-    type Error = std::io::Error;
-  
-    fn load(
-        &self,
-        reader: &mut dyn Reader,
-        _settings: &Self::Settings,
-        _load_context: &mut LoadContext,
-    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
-      Box::pin(async move {
-        let mut bytes = Vec::new();
-        reader.read_to_end(&mut bytes).await?;
-        // TODO: error handling
-        let player = load_gv_from_reader(Cursor::new(bytes));
-        Ok(player.into())
-      })
-    }
-  
-    fn extensions(&self) -> &[&str] {
-      &["gv"]
+/// Send `FrameDecodeError` of `GVMovie` assets
+pub fn send_gv_frame_decode_errors(
+    mut movies: ResMut<Assets<GVMovie>>,
+    mut decode_errors: MessageWriter<FrameDecodeError>,
+) {
+    let ids: Vec<AssetId<GVMovie>> = movies.iter()
+        .filter(|(_, movie)| movie.player.has_decode_errors())
+        .map(|(id, _)| id)
+        .collect();
+    for id in ids {
+        // untracked: taking errors doesn't change the movie
+        let Some(movie) = movies.get_mut_untracked(id) else {
+            continue;
+        };
+        for (frame, error) in movie.player.take_decode_errors() {
+            decode_errors.write(FrameDecodeError { movie: id, frame, error });
+        }
     }
 }
 
@@ -125,12 +257,8 @@ pub fn load_gv(path: &str) -> GVMoviePlayer<BufReader<File>> {
     let file = std::fs::File::open(path).unwrap();
     let reader = BufReader::new(file);
     let gv = GVVideo::load(reader);
-    
-    GVMoviePlayer {
-        gv,
-        blank_mode: BlankMode::default(),
-        state_controller: MoviePlayerStateController::default(),
-    }
+
+    GVMoviePlayer::new(gv)
 }
 
 /// Load a GV video from a reader
@@ -138,12 +266,87 @@ pub fn load_gv_from_reader<R>(reader: R) -> GVMoviePlayer<R>
     where R: Read + Seek
 {
     let gv = GVVideo::load(reader);
-    
-    GVMoviePlayer {
-        gv,
-        blank_mode: BlankMode::default(),
-        state_controller: MoviePlayerStateController::default(),
+
+    GVMoviePlayer::new(gv)
+}
+
+/// Load a GV video from a reader, after `check_gv_integrity`
+pub fn try_load_gv_from_reader<R>(mut reader: R) -> std::io::Result<GVMoviePlayer<R>>
+    where R: Read + Seek
+{
+    check_gv_integrity(&mut reader)?;
+    Ok(load_gv_from_reader(reader))
+}
+
+/// Load a GV video from `GVAssetStream`. Frames not read yet hold the last frame (not decode errors)
+pub fn load_gv_from_asset_stream(stream: GVAssetStream) -> std::io::Result<GVMoviePlayer<GVReader>> {
+    let mut player = try_load_gv_from_reader(GVReader::AssetStream(stream.clone()))?;
+    player.asset_stream = Some(stream);
+    Ok(player)
+}
+
+pub(crate) fn invalid_gv(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// Check header and frame table (`table` at the end of `file_len` bytes)
+pub(crate) fn check_gv_layout(header: &[u8], table: &[u8], file_len: u64) -> std::io::Result<()> {
+    let header = GVHeader::parse(header).ok_or_else(|| invalid_gv("GV header is too short".to_string()))?;
+    header.check().map_err(invalid_gv)?;
+    let frame_count = header.frame_count;
+    if table.len() as u64 != frame_count as u64 * ADDRESS_SIZE_BLOCK_SIZE {
+        return Err(invalid_gv(format!("GV frame table is {} bytes, expected {} frames", table.len(), frame_count)));
+    }
+
+    let table_offset = file_len - table.len() as u64;
+    for (i, block) in table.chunks_exact(ADDRESS_SIZE_BLOCK_SIZE as usize).enumerate() {
+        let address = u64::from_le_bytes(block[..8].try_into().unwrap());
+        let size = u64::from_le_bytes(block[8..].try_into().unwrap());
+        let in_range = address >= HEADER_SIZE && address.checked_add(size).is_some_and(|end| end <= table_offset);
+        if size == 0 || !in_range {
+            return Err(invalid_gv(format!(
+                "GV frame {}: {} bytes at {} is out of frame data ({}..{}), the file may be truncated",
+                i, size, address, HEADER_SIZE, table_offset,
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check header vs. file size, and the frame address table, before loading (`GVVideo::load` panics on broken header).
+/// The reader is rewound to the start.
+pub fn check_gv_integrity<R: Read + Seek>(reader: &mut R) -> std::io::Result<()> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    if file_len < HEADER_SIZE {
+        return Err(invalid_gv(format!("GV file is {} bytes, too small for header", file_len)));
+    }
+    let mut header = vec![0; HEADER_SIZE as usize];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+
+    let frame_count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    let table_len = frame_count as u64 * ADDRESS_SIZE_BLOCK_SIZE;
+    if file_len < HEADER_SIZE + table_len {
+        return Err(invalid_gv(format!("GV file is {} bytes, too small for {} frames", file_len, frame_count)));
     }
+    let mut table = vec![0; table_len as usize];
+    reader.seek(SeekFrom::Start(file_len - table_len))?;
+    reader.read_exact(&mut table)?;
+
+    check_gv_layout(&header, &table, file_len)?;
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
+/// Load a GV video from a memory-mapped file, after `check_gv_integrity`.
+///
+/// NOTE: the file must not be modified while it is mapped
+#[cfg(feature = "gv-mmap")]
+pub fn load_gv_mmap(path: &str) -> std::io::Result<GVMoviePlayer<Cursor<memmap2::Mmap>>> {
+    let file = File::open(path)?;
+    // SAFETY: see above
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    try_load_gv_from_reader(Cursor::new(mmap))
 }
 
 /// Load a GV video from a file (on memory)
@@ -156,12 +359,8 @@ pub fn load_gv_on_memory(path: &str) -> GVMoviePlayer<Cursor<Vec<u8>>> {
     // use cursor
     let reader = Cursor::new(buffer);
     let gv = GVVideo::load(reader);
-    
-    GVMoviePlayer {
-        gv,
-        blank_mode: BlankMode::default(),
-        state_controller: MoviePlayerStateController::default(),
-    }
+
+    GVMoviePlayer::new(gv)
 }
 
 impl<Reader: Read + Seek> MoviePlayer for GVMoviePlayer<Reader> {
@@ -181,6 +380,22 @@ impl<Reader: Read + Seek> MoviePlayer for GVMoviePlayer<Reader> {
         self.gv.get_resolution()
     }
 
+    fn get_metadata(&self) -> MovieMetadata {
+        let format = self.gv.get_format();
+        MovieMetadata {
+            codec: Some(format!("{:?}", format)),
+            container: Some("gv".to_string()),
+            frame_rate: Some(self.gv.get_fps()),
+            // LZ4 compressed frames, so this is average of stored bytes
+            bitrate: Some(self.get_average_bitrate()),
+            bit_depth: Some(8),
+            color_primaries: Some("bt709".to_string()),
+            color_transfer: Some("iec61966-2-1".to_string()),
+            pixel_aspect_ratio: Some((1, 1)),
+            ..Default::default()
+        }
+    }
+
     fn set_volume(&mut self, _volume: f32) {
         warn!("Volume is not supported");
         // do nothing
@@ -202,38 +417,231 @@ impl<Reader: Read + Seek> Blankable for GVMoviePlayer<Reader> {
     }
 }
 
-fn opt_bgra_to_u8(frame_or_not: Option<Vec<u32>>) -> Option<Vec<u8>> {
-    if let Some(frame) = frame_or_not {
-        Some(get_bgra_vec_from_frame(frame))
-    } else {
-        None
+impl<Reader: Read + Seek> GVMoviePlayer<Reader> {
+    fn new(mut gv: GVVideo<Reader>) -> Self {
+        let frame_data_end = get_frame_data_end(&mut gv).unwrap_or(0);
+        Self {
+            gv,
+            blank_mode: BlankMode::default(),
+            state_controller: MoviePlayerStateController::default(),
+            held_frame_compressed: None,
+            held_frame_bgra: None,
+            decode_errors: vec![],
+            last_error_frame: None,
+            asset_stream: None,
+            frame_data_end,
+        }
+    }
+
+    /// Errors of frames (frame, error) since last call. Sent as `FrameDecodeError` for `GVMovie` assets.
+    pub fn take_decode_errors(&mut self) -> Vec<(u32, String)> {
+        std::mem::take(&mut self.decode_errors)
+    }
+
+    pub fn has_decode_errors(&self) -> bool {
+        !self.decode_errors.is_empty()
+    }
+
+    fn get_frame_index(&self) -> u32 {
+        let frame = (self.get_position().as_secs_f64() * self.gv.get_fps() as f64) as u32;
+        frame.min(self.gv.get_frame_count().saturating_sub(1))
+    }
+
+    fn get_last_frame_index(&self) -> u32 {
+        self.gv.get_frame_count().saturating_sub(1)
+    }
+
+    /// Frame shown by `get_compressed_image_data` for the state and blank mode, `None` for blank frames
+    pub(crate) fn get_shown_frame_index(&self) -> Option<u32> {
+        match (self.get_state(), self.blank_mode) {
+            (PlayingState::Playing, _) => Some(self.get_frame_index()),
+            (PlayingState::Paused, BlankMode::Black | BlankMode::White | BlankMode::Transparent) => None,
+            (PlayingState::Paused, _) => Some(self.get_frame_index()),
+            (PlayingState::Stopped, BlankMode::LastFrameOnPause_FirstFrameOnStop) => Some(0),
+            (PlayingState::Stopped, BlankMode::LastFrameOnPauseAndStop) => Some(self.get_last_frame_index()),
+            (PlayingState::Stopped, _) => None,
+        }
+    }
+
+    /// false while the frame is not read yet from `GVAssetStream` (the held frame is shown meanwhile)
+    pub(crate) fn is_frame_ready(&self, frame: u32) -> bool {
+        self.asset_stream.as_ref().is_none_or(|stream| stream.is_frame_ready(frame))
+    }
+
+    /// Read `frame` by `read`, or the held (last good) frame on error.
+    /// Frames which can't be read are recorded as decode errors.
+    /// Frames of `GVAssetStream` which are not read yet are requested, and the held frame is returned meanwhile
+    /// (`WouldBlock` is not a decode error).
+    fn read_or_hold(
+        &mut self,
+        frame: u32,
+        read: fn(&mut GVVideo<Reader>, u32, u64) -> std::io::Result<Vec<u8>>,
+        held: impl Fn(&mut Self) -> &mut Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        if let Some(stream) = &self.asset_stream {
+            // read errors of the stream are reported by `read` below
+            if !stream.is_frame_ready(frame) && stream.get_error().is_none() {
+                stream.prefetch(frame);
+                return held(self).clone();
+            }
+        }
+
+        let error = match read(&mut self.gv, frame, self.frame_data_end) {
+            Ok(data) => {
+                self.last_error_frame = None;
+                *held(self) = Some(data.clone());
+                return Some(data);
+            },
+            // evicted (or not read yet) meanwhile, the stream requests it again
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return held(self).clone(),
+            Err(e) => e.to_string(),
+        };
+
+        // once per frame, not to flood while paused on it
+        if self.last_error_frame != Some(frame) {
+            warn!("failed to read GV frame {}: {}", frame, error);
+            self.last_error_frame = Some(frame);
+            self.decode_errors.push((frame, error));
+        }
+        held(self).clone()
+    }
+
+    fn get_average_bitrate(&self) -> u64 {
+        let total_bytes: u64 = self.gv.address_size_blocks.iter().map(|block| block.size).sum();
+        let duration = self.gv.get_duration().as_secs_f64();
+        if duration > 0.0 {
+            (total_bytes as f64 * 8.0 / duration) as u64
+        } else {
+            0
+        }
+    }
+}
+
+fn get_block_bytes(format: GVFormat) -> usize {
+    match format {
+        GVFormat::DXT1 => 8,
+        _ => 16,
     }
 }
 
+/// Bytes of one uncompressed (BCn) frame. width and height must be multiple of 4
+pub fn get_frame_bytes(width: u32, height: u32, format: GVFormat) -> usize {
+    (width as usize / 4) * (height as usize / 4) * get_block_bytes(format)
+}
+
+/// Start of the frame table (end of frame data)
+fn get_frame_data_end<R: Read + Seek>(gv: &mut GVVideo<R>) -> std::io::Result<u64> {
+    let table_len = gv.address_size_blocks.len() as u64 * ADDRESS_SIZE_BLOCK_SIZE;
+    let file_len = gv.reader.seek(SeekFrom::End(0))?;
+    file_len.checked_sub(table_len)
+        .ok_or_else(|| invalid_gv(format!("GV file is {} bytes, too small for the frame table", file_len)))
+}
+
+/// Read frame `index` as BCn blocks.
+///
+/// Address and size are checked against the frame data (`HEADER_SIZE..frame_data_end`),
+/// and the LZ4 output against the frame size, so broken data is an error instead of a panic in the decoder.
+pub fn read_frame_compressed_checked<R: Read + Seek>(gv: &mut GVVideo<R>, index: u32, frame_data_end: u64) -> std::io::Result<Vec<u8>> {
+    let Some(block) = gv.address_size_blocks.get(index as usize) else {
+        return Err(invalid_gv(format!("no frame {} in {} frames", index, gv.address_size_blocks.len())));
+    };
+    let (address, size) = (block.address, block.size);
+    let in_range = address >= HEADER_SIZE && address.checked_add(size).is_some_and(|end| end <= frame_data_end);
+    if size == 0 || !in_range {
+        return Err(invalid_gv(format!("{} bytes at {} is out of frame data ({}..{})", size, address, HEADER_SIZE, frame_data_end)));
+    }
+
+    let mut compressed = vec![0; size as usize];
+    gv.reader.seek(SeekFrom::Start(address))?;
+    gv.reader.read_exact(&mut compressed)?;
+
+    let (width, height) = gv.get_resolution();
+    let frame_bytes = get_frame_bytes(width, height, gv.get_format());
+    let mut frame = vec![0; frame_bytes];
+    let len = lz4_flex::block::decompress_into(&compressed, &mut frame)
+        .map_err(|e| invalid_gv(format!("failed to decompress: {}", e)))?;
+    if len != frame_bytes {
+        return Err(invalid_gv(format!("{} bytes, expected {}", len, frame_bytes)));
+    }
+    Ok(frame)
+}
+
+/// `read_frame_compressed_checked`, then decode into BGRA
+pub fn read_frame_bgra_checked<R: Read + Seek>(gv: &mut GVVideo<R>, index: u32, frame_data_end: u64) -> std::io::Result<Vec<u8>> {
+    read_frame_compressed_checked(gv, index, frame_data_end)?;
+    gv.read_frame(index)
+        .map(get_bgra_vec_from_frame)
+        .map_err(|e| invalid_gv(format!("{:?}", e)))
+}
+
+/// Check header and that all frames can be read and decompressed into the expected size.
+/// Returns errors (empty if valid).
+pub fn validate_gv_frames<R: Read + Seek>(gv: &mut GVVideo<R>) -> Vec<String> {
+    let (width, height) = gv.get_resolution();
+    if width == 0 || height == 0 || width % 4 != 0 || height % 4 != 0 {
+        return vec![format!("invalid resolution: {}x{}", width, height)];
+    }
+    let frame_count = gv.get_frame_count();
+    let mut errors = vec![];
+    if gv.address_size_blocks.len() != frame_count as usize {
+        errors.push(format!("frame table has {} entries, but header says {} frames", gv.address_size_blocks.len(), frame_count));
+    }
+
+    let frame_data_end = match get_frame_data_end(gv) {
+        Ok(end) => end,
+        Err(e) => {
+            errors.push(e.to_string());
+            return errors;
+        },
+    };
+    for i in 0..frame_count.min(gv.address_size_blocks.len() as u32) {
+        if let Err(e) = read_frame_compressed_checked(gv, i, frame_data_end) {
+            errors.push(format!("frame {}: {}", i, e));
+        }
+    }
+    errors
+}
+
 impl<Reader: Read + Seek> BGRAImageFrameProvider for GVMoviePlayer<Reader> {
     fn get_first_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame(0).ok();
-        opt_bgra_to_u8(frame_or_not)
+        self.read_or_hold(0, read_frame_bgra_checked, |player| &mut player.held_frame_bgra)
     }
 
     fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame(self.gv.get_frame_count() - 1).ok();
-        opt_bgra_to_u8(frame_or_not)
+        let frame = self.get_last_frame_index();
+        self.read_or_hold(frame, read_frame_bgra_checked, |player| &mut player.held_frame_bgra)
     }
 
     fn get_paused_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_at(self.get_position()).ok();
-        opt_bgra_to_u8(frame_or_not)
+        let frame = self.get_frame_index();
+        self.read_or_hold(frame, read_frame_bgra_checked, |player| &mut player.held_frame_bgra)
     }
 
     fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_at(self.get_position()).ok();
-        opt_bgra_to_u8(frame_or_not)
+        let frame = self.get_frame_index();
+        self.read_or_hold(frame, read_frame_bgra_checked, |player| &mut player.held_frame_bgra)
     }
 
+    /// the frame at the current position only: the held frame and decode errors are kept as is.
+    /// `None` if it can't be read, or not read yet from `GVAssetStream` (it is requested then)
+    fn get_grabbed_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        let frame = self.get_frame_index();
+        if let Some(stream) = &self.asset_stream {
+            if !stream.is_frame_ready(frame) {
+                stream.prefetch(frame);
+                return None;
+            }
+        }
+        read_frame_bgra_checked(&mut self.gv, frame, self.frame_data_end).ok()
+    }
+
+    /// GV stores straight alpha
+    fn is_premultiplied_alpha(&self) -> bool {
+        false
+    }
 }
 
-fn get_texture_format_from_gv_format(gv_format: GVFormat) -> TextureFormat {
+pub(crate) fn get_texture_format_from_gv_format(gv_format: GVFormat) -> TextureFormat {
     match gv_format {
         GVFormat::DXT1 => TextureFormat::Bc1RgbaUnormSrgb,
         GVFormat::DXT3 => TextureFormat::Bc2RgbaUnormSrgb,
@@ -244,23 +652,22 @@ fn get_texture_format_from_gv_format(gv_format: GVFormat) -> TextureFormat {
 
 impl<Reader: Read + Seek> CompressedImageFrameProvider for GVMoviePlayer<Reader> {
     fn get_first_frame_compressed(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_compressed(0).ok();
-        frame_or_not
+        self.read_or_hold(0, read_frame_compressed_checked, |player| &mut player.held_frame_compressed)
     }
 
     fn get_last_frame_compressed(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_compressed(self.gv.get_frame_count() - 1).ok();
-        frame_or_not
+        let frame = self.get_last_frame_index();
+        self.read_or_hold(frame, read_frame_compressed_checked, |player| &mut player.held_frame_compressed)
     }
 
     fn get_playing_frame_compressed(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_compressed_at(self.get_position()).ok();
-        frame_or_not
+        let frame = self.get_frame_index();
+        self.read_or_hold(frame, read_frame_compressed_checked, |player| &mut player.held_frame_compressed)
     }
 
     fn get_paused_frame_compressed(&mut self) -> Option<Vec<u8>> {
-        let frame_or_not = self.gv.read_frame_compressed_at(self.get_position()).ok();
-        frame_or_not
+        let frame = self.get_frame_index();
+        self.read_or_hold(frame, read_frame_compressed_checked, |player| &mut player.held_frame_compressed)
     }
 
     fn get_texture_format(&self) -> TextureFormat {
@@ -281,6 +688,197 @@ mod tests {
         movie.stop();
     }
 
+    #[test]
+    fn metadata() {
+        let movie = load_gv("assets/test.gv");
+        let metadata = movie.get_metadata();
+        assert_eq!(metadata.codec, Some("DXT1".to_string()));
+        assert_eq!(metadata.container, Some("gv".to_string()));
+        assert_eq!(metadata.frame_rate, Some(30.0));
+        assert_eq!(metadata.get_rotation_degrees(), 0.0);
+    }
+
+    #[test]
+    fn validate_frames() {
+        let mut movie = load_gv("assets/test.gv");
+        assert!(validate_gv_frames(&mut movie.gv).is_empty());
+
+        // break the first frame
+        let mut bytes = std::fs::read("assets/test.gv").unwrap();
+        bytes[24..88].fill(0xFF);
+        let mut movie = load_gv_from_reader(Cursor::new(bytes));
+        let errors = validate_gv_frames(&mut movie.gv);
+        assert!(errors[0].starts_with("frame 0:"), "{:?}", errors);
+    }
+
+    /// (address, size) of a frame in GV bytes
+    fn frame_block(bytes: &[u8], frame: usize) -> (usize, usize) {
+        let frame_count = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let entry = bytes.len() - (frame_count - frame) * 16;
+        let address = u64::from_le_bytes(bytes[entry..entry + 8].try_into().unwrap());
+        let size = u64::from_le_bytes(bytes[entry + 8..entry + 16].try_into().unwrap());
+        (address as usize, size as usize)
+    }
+
+    /// GV bytes with the first frame repeated `count` times
+    fn repeat_first_frame(bytes: &[u8], count: u32) -> Vec<u8> {
+        let (address, size) = frame_block(bytes, 0);
+        let mut repeated = bytes[..HEADER_SIZE as usize].to_vec();
+        repeated[8..12].copy_from_slice(&count.to_le_bytes());
+        let mut table = vec![];
+        for _ in 0..count {
+            table.extend_from_slice(&(repeated.len() as u64).to_le_bytes());
+            table.extend_from_slice(&(size as u64).to_le_bytes());
+            repeated.extend_from_slice(&bytes[address..address + size]);
+        }
+        repeated.extend_from_slice(&table);
+        repeated
+    }
+
+    #[test]
+    fn integrity() {
+        let bytes = std::fs::read("assets/test.gv").unwrap();
+        assert!(try_load_gv_from_reader(Cursor::new(bytes.clone())).is_ok());
+
+        // truncated
+        let truncated = bytes[..bytes.len() / 2].to_vec();
+        assert!(try_load_gv_from_reader(Cursor::new(truncated)).is_err());
+        assert!(try_load_gv_from_reader(Cursor::new(bytes[..10].to_vec())).is_err());
+
+        // frame table points out of the file
+        let mut broken = bytes.clone();
+        let entry = broken.len() - 16;
+        broken[entry..entry + 8].copy_from_slice(&(bytes.len() as u64).to_le_bytes());
+        let error = try_load_gv_from_reader(Cursor::new(broken)).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        // no frames
+        assert!(try_load_gv_from_reader(Cursor::new(repeat_first_frame(&bytes, 2))).is_ok());
+        let error = try_load_gv_from_reader(Cursor::new(repeat_first_frame(&bytes, 0))).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn hold_last_good_frame() {
+        // break the second frame
+        let mut bytes = repeat_first_frame(&std::fs::read("assets/test.gv").unwrap(), 2);
+        let (address, size) = frame_block(&bytes, 1);
+        bytes[address..address + size].fill(0xFF);
+        let mut movie = try_load_gv_from_reader(Cursor::new(bytes)).unwrap();
+        let fps = movie.gv.get_fps() as f64;
+
+        movie.play();
+        movie.pause();
+        let first_frame = movie.get_paused_frame_compressed();
+        assert!(first_frame.is_some());
+        assert!(!movie.has_decode_errors());
+
+        movie.seek(Duration::from_secs_f64(1.5 / fps)).unwrap();
+        assert_eq!(movie.get_paused_frame_compressed(), first_frame);
+        // reported once
+        assert_eq!(movie.get_paused_frame_compressed(), first_frame);
+        let errors = movie.take_decode_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+    }
+
+    /// Reader which returns `WouldBlock` in `blocked`, like `GVAssetStream` for frames not read yet
+    struct BlockingReader {
+        inner: Cursor<Vec<u8>>,
+        blocked: std::ops::Range<u64>,
+    }
+
+    impl Read for BlockingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.blocked.contains(&self.inner.position()) {
+                return Err(std::io::Error::new(std::io::ErrorKind::WouldBlock, "not read yet"));
+            }
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for BlockingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn would_block_is_not_error() {
+        let bytes = repeat_first_frame(&std::fs::read("assets/test.gv").unwrap(), 2);
+        let (address, size) = frame_block(&bytes, 1);
+        let reader = BlockingReader {
+            inner: Cursor::new(bytes),
+            blocked: address as u64..(address + size) as u64,
+        };
+        let mut movie = try_load_gv_from_reader(reader).unwrap();
+        let fps = movie.gv.get_fps() as f64;
+
+        movie.play();
+        movie.pause();
+        let first_frame = movie.get_paused_frame_compressed();
+        assert!(first_frame.is_some());
+
+        movie.seek(Duration::from_secs_f64(1.5 / fps)).unwrap();
+        assert_eq!(movie.get_paused_frame_compressed(), first_frame);
+        assert!(!movie.has_decode_errors());
+
+        // read once it's available
+        movie.gv.reader.blocked = 0..0;
+        assert!(movie.get_paused_frame_compressed().is_some());
+        assert!(!movie.has_decode_errors());
+    }
+
+    #[test]
+    fn grab_frame() {
+        use crate::thumbnail::FrameGrabber;
+
+        // break the second frame
+        let mut bytes = repeat_first_frame(&std::fs::read("assets/test.gv").unwrap(), 2);
+        let (address, size) = frame_block(&bytes, 1);
+        bytes[address..address + size].fill(0xFF);
+        let mut movie = try_load_gv_from_reader(Cursor::new(bytes)).unwrap();
+        let fps = movie.gv.get_fps() as f64;
+
+        let first_frame = movie.get_frame_at(Duration::ZERO).unwrap();
+        assert_eq!(first_frame.resolution, (640, 360));
+        // the broken frame is not replaced by a held frame, and not reported
+        assert!(movie.get_frame_at(Duration::from_secs_f64(1.5 / fps)).is_none());
+        assert!(!movie.has_decode_errors());
+        assert!(movie.held_frame_bgra.is_none());
+    }
+
+    #[test]
+    fn load_modes() {
+        let disk = GVReader::open("assets/test.gv", LoadMode::DiskStream).unwrap();
+        assert!(matches!(disk, GVReader::DiskStream(_)));
+        let mut disk = load_gv_from_reader(disk);
+        let mut on_memory = load_gv_from_reader(GVReader::open("assets/test.gv", LoadMode::OnMemory).unwrap());
+        assert_eq!(disk.get_resolution(), on_memory.get_resolution());
+        assert_eq!(disk.get_last_frame_compressed(), on_memory.get_last_frame_compressed());
+        assert!(GVReader::open("assets/not_found.gv", LoadMode::OnMemory).is_err());
+    }
+
+    #[cfg(feature = "gv-mmap")]
+    #[test]
+    fn memory_mapped() {
+        let mut mapped = load_gv_mmap("assets/test.gv").unwrap();
+        let mut on_memory = load_gv_on_memory("assets/test.gv");
+        assert_eq!(mapped.get_resolution(), on_memory.get_resolution());
+        assert_eq!(mapped.get_last_frame_compressed(), on_memory.get_last_frame_compressed());
+
+        let reader = GVReader::open("assets/test.gv", LoadMode::MemoryMapped).unwrap();
+        assert!(matches!(reader, GVReader::MemoryMapped(_)));
+
+        // checked like other readers
+        let path = std::env::temp_dir().join(format!("gv_mmap_truncated_{}.gv", std::process::id()));
+        let bytes = std::fs::read("assets/test.gv").unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        let error = load_gv_mmap(path.to_str().unwrap()).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
     // TODO: add duration test
     // TODO: add loop test
     // TODO: add seek test
@@ -0,0 +1,453 @@
+use bevy::asset::io::AssetSource;
+use bevy::asset::io::Reader;
+use bevy::asset::AssetPath;
+use bevy::asset::AssetServer;
+use bevy::asset::AsyncReadExt;
+use bevy::asset::AsyncSeekExt;
+use bevy::log::warn;
+use bevy::tasks::futures_lite::future::poll_fn;
+use bevy::tasks::IoTaskPool;
+
+use crate::gv::check_gv_layout;
+use crate::gv::ADDRESS_SIZE_BLOCK_SIZE;
+use crate::gv::HEADER_SIZE;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::task::Poll;
+use std::task::Waker;
+
+/// Frames read ahead of the requested frame
+pub const DEFAULT_PREFETCH_FRAMES: u32 = 16;
+/// Error message of frames which are not read yet (`WouldBlock`)
+pub const FRAME_NOT_READ_YET: &str = "GV frame is not read yet";
+
+/// GV disk stream through bevy `AssetReader`, so it works on any asset source (and never blocks on slow disks).
+///
+/// Header and frame table are read (and checked) on load. Frames are read on the IO task pool when requested (with prefetch),
+/// by one reader of the asset, which is kept open while the stream is alive.
+/// A frame which is not read yet returns `WouldBlock` (the player holds the last frame meanwhile).
+///
+/// Clones share read frames and the reader (and have their own position).
+#[derive(Clone)]
+pub struct GVAssetStream {
+    state: Arc<Mutex<StreamState>>,
+    position: u64,
+    prefetch_frames: u32,
+}
+
+/// Frames `first..first + count`, `len` bytes at `start`
+struct ReadRequest {
+    first: u32,
+    count: u32,
+    start: u64,
+    len: usize,
+}
+
+struct StreamState {
+    header: Vec<u8>,
+    table_offset: u64,
+    table: Vec<u8>,
+    /// (address, size) of each frame
+    blocks: Vec<(u64, u64)>,
+    /// compressed frames by index
+    frames: HashMap<u32, Vec<u8>>,
+    pending: HashSet<u32>,
+    error: Option<String>,
+    /// for the reader task
+    requests: VecDeque<ReadRequest>,
+    /// wakes the reader task on a request, or when the stream is dropped
+    waker: Option<Waker>,
+}
+
+impl Drop for StreamState {
+    fn drop(&mut self) {
+        // let the reader task exit
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl StreamState {
+    fn file_len(&self) -> u64 {
+        self.table_offset + self.table.len() as u64
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.blocks.len() as u32
+    }
+
+    /// Insert frames `first..first + count` from `bytes` (starting at the address of `first`)
+    fn insert_frames(&mut self, first: u32, count: u32, bytes: &[u8]) {
+        let start = self.blocks[first as usize].0;
+        for i in first..first + count {
+            let (address, size) = self.blocks[i as usize];
+            let offset = (address - start) as usize;
+            self.frames.insert(i, bytes[offset..offset + size as usize].to_vec());
+        }
+    }
+}
+
+/// Read `len` bytes at `start` by `reader` (opened on first use, and again after an error)
+async fn read_range<'a>(
+    source: &'a AssetSource,
+    path: &'a AssetPath<'static>,
+    reader: &mut Option<Box<dyn Reader + 'a>>,
+    start: u64,
+    len: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut current = match reader.take() {
+        Some(current) => current,
+        None => source.reader().read(path.path()).await.map_err(std::io::Error::other)?,
+    };
+    let mut bytes = vec![0; len];
+    let seekable = current.seekable().map_err(std::io::Error::other)?;
+    seekable.seek(SeekFrom::Start(start)).await?;
+    seekable.read_exact(&mut bytes).await?;
+    *reader = Some(current);
+    Ok(bytes)
+}
+
+/// Wait for the next request. `None` once all clones of the stream are dropped
+fn next_request(state: &Weak<Mutex<StreamState>>) -> impl Future<Output = Option<ReadRequest>> + '_ {
+    poll_fn(move |cx| {
+        let Some(shared) = state.upgrade() else {
+            return Poll::Ready(None);
+        };
+        let mut stream = shared.lock().unwrap();
+        match stream.requests.pop_front() {
+            Some(request) => Poll::Ready(Some(request)),
+            None => {
+                stream.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    })
+}
+
+/// Reader task of a stream: reads requested frames with one reader of the asset
+async fn read_requests(state: Weak<Mutex<StreamState>>, asset_server: AssetServer, path: AssetPath<'static>) {
+    let source = asset_server.get_source(path.source()).map_err(|e| e.to_string());
+    let mut reader = None;
+    while let Some(ReadRequest { first, count, start, len }) = next_request(&state).await {
+        let result = match &source {
+            Ok(source) => read_range(source, &path, &mut reader, start, len).await,
+            Err(e) => Err(std::io::Error::other(e.clone())),
+        };
+
+        let Some(shared) = state.upgrade() else {
+            return;
+        };
+        let mut stream = shared.lock().unwrap();
+        for i in first..first + count {
+            stream.pending.remove(&i);
+        }
+        match result {
+            Ok(bytes) => {
+                stream.insert_frames(first, count, &bytes);
+                stream.error = None;
+            },
+            Err(e) => {
+                if stream.error.is_none() {
+                    warn!("failed to read GV frames of {}: {}", path, e);
+                }
+                stream.error = Some(e.to_string());
+            },
+        }
+    }
+}
+
+impl GVAssetStream {
+    /// Read header, frame table and the first frames from `reader` (the asset being loaded).
+    /// `reader` must be seekable.
+    pub async fn load(
+        reader: &mut dyn Reader,
+        asset_server: AssetServer,
+        path: AssetPath<'static>,
+        prefetch_frames: u32,
+    ) -> std::io::Result<Self> {
+        let reader = reader.seekable().map_err(std::io::Error::other)?;
+        let prefetch_frames = prefetch_frames.max(1);
+
+        let mut header = vec![0; HEADER_SIZE as usize];
+        reader.seek(SeekFrom::Start(0)).await?;
+        reader.read_exact(&mut header).await?;
+        let frame_count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+
+        let file_len = reader.seek(SeekFrom::End(0)).await?;
+        let table_len = frame_count as u64 * ADDRESS_SIZE_BLOCK_SIZE;
+        if file_len < HEADER_SIZE + table_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("GV file is {} bytes, too small for {} frames", file_len, frame_count),
+            ));
+        }
+        let table_offset = file_len - table_len;
+        let mut table = vec![0; table_len as usize];
+        reader.seek(SeekFrom::Start(table_offset)).await?;
+        reader.read_exact(&mut table).await?;
+        check_gv_layout(&header, &table, file_len)?;
+        let blocks: Vec<(u64, u64)> = table.chunks_exact(ADDRESS_SIZE_BLOCK_SIZE as usize)
+            .map(|b| (u64::from_le_bytes(b[..8].try_into().unwrap()), u64::from_le_bytes(b[8..].try_into().unwrap())))
+            .collect();
+
+        let mut state = StreamState {
+            header,
+            table_offset,
+            table,
+            blocks,
+            frames: HashMap::new(),
+            pending: HashSet::new(),
+            error: None,
+            requests: VecDeque::new(),
+            waker: None,
+        };
+
+        // first frames, so the first frame can be shown without waiting
+        let count = prefetch_frames.min(frame_count);
+        if count > 0 {
+            let start = state.blocks[0].0;
+            let (last_address, last_size) = state.blocks[count as usize - 1];
+            let mut bytes = vec![0; (last_address + last_size - start) as usize];
+            reader.seek(SeekFrom::Start(start)).await?;
+            reader.read_exact(&mut bytes).await?;
+            state.insert_frames(0, count, &bytes);
+        }
+
+        let state = Arc::new(Mutex::new(state));
+        IoTaskPool::get().spawn(read_requests(Arc::downgrade(&state), asset_server, path)).detach();
+
+        Ok(Self {
+            state,
+            position: 0,
+            prefetch_frames,
+        })
+    }
+
+    pub fn get_prefetch_frames(&self) -> u32 {
+        self.prefetch_frames
+    }
+
+    pub fn set_prefetch_frames(&mut self, prefetch_frames: u32) {
+        self.prefetch_frames = prefetch_frames.max(1);
+    }
+
+    /// true if the frame is read (and not evicted)
+    pub fn is_frame_ready(&self, index: u32) -> bool {
+        self.state.lock().unwrap().frames.contains_key(&index)
+    }
+
+    /// last read error, if any
+    pub fn get_error(&self) -> Option<String> {
+        self.state.lock().unwrap().error.clone()
+    }
+
+    /// Request `index` and following frames (wraps around for loop), and evict frames far from it.
+    pub fn prefetch(&self, index: u32) {
+        let mut state = self.state.lock().unwrap();
+        let frame_count = state.frame_count();
+        if index >= frame_count {
+            return;
+        }
+
+        let prefetch = self.prefetch_frames.min(frame_count);
+        // keep first / last frame (used when stopped), and a few frames behind for seeking back
+        state.frames.retain(|&i, _| {
+            let ahead = (i + frame_count - index) % frame_count;
+            let behind = (index + frame_count - i) % frame_count;
+            ahead < prefetch * 2 || behind <= prefetch || i == 0 || i == frame_count - 1
+        });
+
+        let missing: Vec<u32> = (0..prefetch)
+            .map(|k| (index + k) % frame_count)
+            .filter(|i| !state.frames.contains_key(i) && !state.pending.contains(i))
+            .collect();
+        // don't read frame by frame while the window is mostly filled
+        if missing.is_empty() || (state.frames.contains_key(&index) && missing.len() < (prefetch / 2) as usize) {
+            return;
+        }
+
+        // contiguous runs (split at wrap around), one read each
+        let mut runs: Vec<(u32, u32)> = vec![];
+        for &i in &missing {
+            state.pending.insert(i);
+            match runs.last_mut() {
+                Some((first, count)) if *first + *count == i => *count += 1,
+                _ => runs.push((i, 1)),
+            }
+        }
+        for (first, count) in runs {
+            let start = state.blocks[first as usize].0;
+            let (last_address, last_size) = state.blocks[(first + count - 1) as usize];
+            let len = (last_address + last_size - start) as usize;
+            state.requests.push_back(ReadRequest { first, count, start, len });
+        }
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Copy `bytes` from `offset` into `buf`
+fn copy_at(buf: &mut [u8], bytes: &[u8], offset: u64) -> usize {
+    let offset = offset as usize;
+    let n = buf.len().min(bytes.len() - offset);
+    buf[..n].copy_from_slice(&bytes[offset..offset + n]);
+    n
+}
+
+impl Read for GVAssetStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let position = self.position;
+        let result = {
+            let state = self.state.lock().unwrap();
+            if position < state.header.len() as u64 {
+                Ok((copy_at(buf, &state.header, position), None))
+            } else if position >= state.file_len() {
+                return Ok(0);
+            } else if position >= state.table_offset {
+                Ok((copy_at(buf, &state.table, position - state.table_offset), None))
+            } else {
+                // frame containing the position (blocks are sorted by address)
+                let index = state.blocks.partition_point(|&(address, _)| address <= position);
+                let block = index.checked_sub(1).map(|i| state.blocks[i]);
+                let Some((address, _)) = block.filter(|&(address, size)| position < address + size) else {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "position is not in any GV frame"));
+                };
+                let index = index as u32 - 1;
+                match state.frames.get(&index) {
+                    Some(frame) => Ok((copy_at(buf, frame, position - address), Some(index))),
+                    None => Err((index, state.error.clone())),
+                }
+            }
+        };
+
+        match result {
+            Ok((n, frame_index)) => {
+                self.position += n as u64;
+                // keep reading ahead while playing
+                if let Some(index) = frame_index {
+                    self.prefetch(index);
+                }
+                Ok(n)
+            },
+            Err((index, error)) => {
+                self.prefetch(index);
+                Err(match error {
+                    Some(e) => std::io::Error::other(e),
+                    None => std::io::Error::new(std::io::ErrorKind::WouldBlock, FRAME_NOT_READ_YET),
+                })
+            },
+        }
+    }
+}
+
+impl Seek for GVAssetStream {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let len = self.state.lock().unwrap().file_len();
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek to a negative position")
+        })?;
+        Ok(self.position)
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gv::load_gv_from_asset_stream;
+    use crate::gv::load_gv_from_reader;
+    use crate::blankable_image_data_provider::CompressedImageFrameProvider;
+    use crate::movie_player::MoviePlayer;
+    use bevy::prelude::*;
+    use std::time::Duration;
+
+    /// Poll `read` until it returns a frame (frames are read in background)
+    fn poll_frame(mut read: impl FnMut() -> Option<Vec<u8>>) -> Option<Vec<u8>> {
+        for _ in 0..100 {
+            if let Some(frame) = read() {
+                return Some(frame);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[cfg(feature = "gv-encode")]
+    #[test]
+    fn stream_frames() {
+        use crate::gv_encode::GVEncoder;
+        use gv_video::GVFormat;
+        use std::io::Cursor;
+
+        // 6 frames of different gray levels
+        let mut encoder = GVEncoder::new(Cursor::new(Vec::new()), 8, 8, 30.0, GVFormat::DXT1).unwrap();
+        for i in 0..6u8 {
+            encoder.add_frame_bgra(&[i * 40, i * 40, i * 40, 0xFF].repeat(64)).unwrap();
+        }
+        let bytes = encoder.finish().unwrap().into_inner();
+        let dir = std::env::temp_dir().join(format!("gv_asset_stream_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("stream.gv"), &bytes).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin { file_path: dir.to_string_lossy().to_string(), ..default() });
+        let asset_server = app.world().resource::<AssetServer>().clone();
+
+        let path = AssetPath::from("stream.gv");
+        let stream = bevy::tasks::block_on(async {
+            let source = asset_server.get_source(path.source()).unwrap();
+            let mut reader = source.reader().read(path.path()).await.unwrap();
+            GVAssetStream::load(&mut *reader, asset_server.clone(), path.clone(), 2).await.unwrap()
+        });
+        assert!(stream.is_frame_ready(0));
+        assert!(stream.is_frame_ready(1));
+        assert!(!stream.is_frame_ready(2));
+        assert!(!stream.is_frame_ready(5));
+
+        let mut streamed = load_gv_from_asset_stream(stream).unwrap();
+        let mut on_memory = load_gv_from_reader(Cursor::new(bytes));
+        assert_eq!(streamed.get_resolution(), on_memory.get_resolution());
+        let first_frame = streamed.get_first_frame_compressed();
+        assert_eq!(first_frame, on_memory.get_first_frame_compressed());
+
+        // last frame is read in background, the first frame is held meanwhile
+        assert_eq!(streamed.get_last_frame_compressed(), first_frame);
+        let last_frame = poll_frame(|| streamed.get_last_frame_compressed().filter(|frame| Some(frame) != first_frame.as_ref()));
+        assert!(last_frame.is_some());
+        assert_eq!(last_frame, on_memory.get_last_frame_compressed());
+
+        // seek to a frame not read yet: the last frame is held until it's read
+        streamed.play();
+        on_memory.play();
+        let first_frame = streamed.get_playing_frame_compressed();
+        assert_eq!(first_frame, on_memory.get_first_frame_compressed());
+        let position = Duration::from_secs_f64(3.5 / 30.0);
+        streamed.seek(position).unwrap();
+        on_memory.seek(position).unwrap();
+        assert_eq!(streamed.get_playing_frame_compressed(), first_frame);
+        let expected = on_memory.get_playing_frame_compressed();
+        let frame = poll_frame(|| streamed.get_playing_frame_compressed().filter(|frame| Some(frame) != first_frame.as_ref()));
+        assert_eq!(frame, expected);
+        // not read yet is not an error
+        assert!(!streamed.has_decode_errors());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
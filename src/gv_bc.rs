@@ -0,0 +1,272 @@
+use gv_video::get_bgra_vec_from_frame;
+use gv_video::GVFormat;
+
+use crate::gv::get_frame_bytes;
+use crate::gv::invalid_gv;
+
+/// Pad BGRA frame with transparent pixels to multiple of 4, returns (data, width, height)
+pub fn pad_bgra_to_multiple_of_4(bgra: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    let padded_width = width.div_ceil(4) * 4;
+    let padded_height = height.div_ceil(4) * 4;
+    if padded_width == width && padded_height == height {
+        return (bgra.to_vec(), width, height);
+    }
+    let mut padded = vec![0u8; (padded_width * padded_height * 4) as usize];
+    let row_bytes = (width * 4) as usize;
+    for y in 0..height as usize {
+        let dst = y * padded_width as usize * 4;
+        padded[dst..dst + row_bytes].copy_from_slice(&bgra[y * row_bytes..(y + 1) * row_bytes]);
+    }
+    (padded, padded_width, padded_height)
+}
+
+/// Compress BGRA8 frame into BCn blocks (width and height must be multiple of 4)
+pub fn compress_bgra(bgra: &[u8], width: u32, height: u32, format: GVFormat) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let mut blocks = Vec::with_capacity(get_frame_bytes(width as u32, height as u32, format));
+
+    for by in 0..height / 4 {
+        for bx in 0..width / 4 {
+            // RGBA pixels of the block
+            let mut pixels = [[0u8; 4]; 16];
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let offset = ((by * 4 + i / 4) * width + bx * 4 + i % 4) * 4;
+                *pixel = [bgra[offset + 2], bgra[offset + 1], bgra[offset], bgra[offset + 3]];
+            }
+            match format {
+                GVFormat::DXT1 => blocks.extend_from_slice(&compress_bc1_block(&pixels, true)),
+                GVFormat::DXT3 => {
+                    blocks.extend_from_slice(&compress_bc2_alpha(&pixels));
+                    blocks.extend_from_slice(&compress_bc1_block(&pixels, false));
+                },
+                GVFormat::DXT5 => {
+                    blocks.extend_from_slice(&compress_bc3_alpha(&pixels));
+                    blocks.extend_from_slice(&compress_bc1_block(&pixels, false));
+                },
+                GVFormat::BC7 => blocks.extend_from_slice(&compress_bc7_mode6_block(&pixels)),
+            }
+        }
+    }
+    blocks
+}
+
+/// Decode BCn blocks (decompressed GV frame) into BGRA
+pub fn decompress_bgra(blocks: &[u8], width: u32, height: u32, format: GVFormat) -> std::io::Result<Vec<u8>> {
+    let (w, h) = (width as usize, height as usize);
+    let mut pixels = vec![0u32; w * h];
+    match format {
+        GVFormat::DXT1 => texture2ddecoder::decode_bc1(blocks, w, h, &mut pixels),
+        GVFormat::DXT3 => texture2ddecoder::decode_bc2(blocks, w, h, &mut pixels),
+        GVFormat::DXT5 => texture2ddecoder::decode_bc3(blocks, w, h, &mut pixels),
+        GVFormat::BC7 => texture2ddecoder::decode_bc7(blocks, w, h, &mut pixels),
+    }
+    .map_err(|e| invalid_gv(e.to_string()))?;
+    Ok(get_bgra_vec_from_frame(pixels))
+}
+
+fn to_rgb565(c: [u8; 3]) -> u16 {
+    ((c[0] as u16 >> 3) << 11) | ((c[1] as u16 >> 2) << 5) | (c[2] as u16 >> 3)
+}
+
+fn from_rgb565(c: u16) -> [u8; 3] {
+    let r = ((c >> 11) & 0x1F) as u8;
+    let g = ((c >> 5) & 0x3F) as u8;
+    let b = (c & 0x1F) as u8;
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+fn distance_rgb(a: [u8; 3], b: [u8; 3]) -> u32 {
+    (0..3).map(|i| (a[i] as i32 - b[i] as i32).pow(2) as u32).sum()
+}
+
+/// BC1 color block. with `allow_transparent`, pixels with alpha < 128 use 1-bit alpha (3 color mode)
+fn compress_bc1_block(pixels: &[[u8; 4]; 16], allow_transparent: bool) -> [u8; 8] {
+    let has_transparent = allow_transparent && pixels.iter().any(|p| p[3] < 128);
+    let opaque: Vec<[u8; 3]> = pixels.iter()
+        .filter(|p| !has_transparent || p[3] >= 128)
+        .map(|p| [p[0], p[1], p[2]])
+        .collect();
+
+    // endpoints: bounding box of the colors
+    let mut min = [255u8; 3];
+    let mut max = [0u8; 3];
+    for c in &opaque {
+        for i in 0..3 {
+            min[i] = min[i].min(c[i]);
+            max[i] = max[i].max(c[i]);
+        }
+    }
+    if opaque.is_empty() {
+        min = [0; 3];
+        max = [0; 3];
+    }
+    let mut c0 = to_rgb565(max);
+    let mut c1 = to_rgb565(min);
+
+    // c0 > c1: 4 colors, c0 <= c1: 3 colors + transparent
+    if has_transparent {
+        if c0 > c1 {
+            std::mem::swap(&mut c0, &mut c1);
+        }
+    } else if c0 < c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    } else if c0 == c1 {
+        // solid color: all indices 0
+        let mut block = [0u8; 8];
+        block[0..2].copy_from_slice(&c0.to_le_bytes());
+        block[2..4].copy_from_slice(&c1.to_le_bytes());
+        return block;
+    }
+
+    let e0 = from_rgb565(c0);
+    let e1 = from_rgb565(c1);
+    let lerp = |a: u8, b: u8, wa: u32, wb: u32| ((a as u32 * wa + b as u32 * wb) / (wa + wb)) as u8;
+    let palette: Vec<[u8; 3]> = if c0 > c1 {
+        vec![
+            e0,
+            e1,
+            [0, 1, 2].map(|i| lerp(e0[i], e1[i], 2, 1)),
+            [0, 1, 2].map(|i| lerp(e0[i], e1[i], 1, 2)),
+        ]
+    } else {
+        vec![e0, e1, [0, 1, 2].map(|i| lerp(e0[i], e1[i], 1, 1))]
+    };
+
+    let mut indices = 0u32;
+    for (i, p) in pixels.iter().enumerate() {
+        let index = if has_transparent && p[3] < 128 {
+            3
+        } else {
+            let color = [p[0], p[1], p[2]];
+            (0..palette.len()).min_by_key(|&j| distance_rgb(color, palette[j])).unwrap() as u32
+        };
+        indices |= index << (i * 2);
+    }
+
+    let mut block = [0u8; 8];
+    block[0..2].copy_from_slice(&c0.to_le_bytes());
+    block[2..4].copy_from_slice(&c1.to_le_bytes());
+    block[4..8].copy_from_slice(&indices.to_le_bytes());
+    block
+}
+
+/// BC2 (DXT3) explicit 4-bit alpha
+fn compress_bc2_alpha(pixels: &[[u8; 4]; 16]) -> [u8; 8] {
+    let mut alpha = 0u64;
+    for (i, p) in pixels.iter().enumerate() {
+        alpha |= ((p[3] as u64 * 15 + 127) / 255) << (i * 4);
+    }
+    alpha.to_le_bytes()
+}
+
+/// BC3 (DXT5) interpolated alpha (8 levels)
+fn compress_bc3_alpha(pixels: &[[u8; 4]; 16]) -> [u8; 8] {
+    let a0 = pixels.iter().map(|p| p[3]).max().unwrap();
+    let a1 = pixels.iter().map(|p| p[3]).min().unwrap();
+
+    let mut block = [0u8; 8];
+    block[0] = a0;
+    block[1] = a1;
+    if a0 == a1 {
+        return block;
+    }
+    let palette: Vec<u8> = (0..8u32)
+        .map(|i| match i {
+            0 => a0,
+            1 => a1,
+            _ => (((8 - i) * a0 as u32 + (i - 1) * a1 as u32) / 7) as u8,
+        })
+        .collect();
+
+    let mut indices = 0u64;
+    for (i, p) in pixels.iter().enumerate() {
+        let index = (0..8).min_by_key(|&j| (palette[j] as i32 - p[3] as i32).abs()).unwrap() as u64;
+        indices |= index << (i * 3);
+    }
+    block[2..8].copy_from_slice(&indices.to_le_bytes()[0..6]);
+    block
+}
+
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// BC7 mode 6 (1 subset, RGBA 7-bit endpoints + p-bit, 4-bit indices)
+fn compress_bc7_mode6_block(pixels: &[[u8; 4]; 16]) -> [u8; 16] {
+    let mut min = [255u8; 4];
+    let mut max = [0u8; 4];
+    for p in pixels {
+        for i in 0..4 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+
+    // 8-bit endpoint = (7-bit value << 1) | p-bit, p-bit is shared by channels of the endpoint
+    let quantize = |c: [u8; 4]| -> ([u8; 4], u8) {
+        [0u8, 1]
+            .map(|p| (c.map(|v| ((v as i32 - p as i32 + 1) / 2).clamp(0, 127) as u8), p))
+            .into_iter()
+            .min_by_key(|(q, p)| (0..4).map(|i| (((q[i] << 1) | p) as i32 - c[i] as i32).pow(2)).sum::<i32>())
+            .unwrap()
+    };
+    let (mut q0, mut p0) = quantize(min);
+    let (mut q1, mut p1) = quantize(max);
+    let endpoint = |q: [u8; 4], p: u8| q.map(|v| (v << 1) | p);
+
+    let palette = |e0: [u8; 4], e1: [u8; 4]| -> Vec<[u8; 4]> {
+        BC7_WEIGHTS_4.iter()
+            .map(|&w| [0, 1, 2, 3].map(|i| (((64 - w) * e0[i] as u32 + w * e1[i] as u32 + 32) >> 6) as u8))
+            .collect()
+    };
+    let distance = |a: [u8; 4], b: [u8; 4]| -> u32 { (0..4).map(|i| (a[i] as i32 - b[i] as i32).pow(2) as u32).sum() };
+
+    let colors = palette(endpoint(q0, p0), endpoint(q1, p1));
+    let mut indices: Vec<u8> = pixels.iter()
+        .map(|p| (0..16).min_by_key(|&j| distance(*p, colors[j])).unwrap() as u8)
+        .collect();
+
+    // anchor (first) index must have msb 0: swap endpoints and invert indices
+    if indices[0] >= 8 {
+        std::mem::swap(&mut q0, &mut q1);
+        std::mem::swap(&mut p0, &mut p1);
+        for index in indices.iter_mut() {
+            *index = 15 - *index;
+        }
+    }
+
+    let mut bits = 0u128;
+    let mut offset = 0;
+    let mut push = |value: u128, count: u32| {
+        bits |= value << offset;
+        offset += count;
+    };
+    push(1 << 6, 7); // mode 6
+    for i in 0..4 {
+        push(q0[i] as u128, 7);
+        push(q1[i] as u128, 7);
+    }
+    push(p0 as u128, 1);
+    push(p1 as u128, 1);
+    for (i, &index) in indices.iter().enumerate() {
+        push(index as u128, if i == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes()
+}
+
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompress_solid() {
+        let bgra = [10, 120, 240, 255].repeat(4 * 4);
+        for format in [GVFormat::DXT1, GVFormat::DXT3, GVFormat::DXT5, GVFormat::BC7] {
+            let decoded = decompress_bgra(&compress_bgra(&bgra, 4, 4, format), 4, 4, format).unwrap();
+            assert_eq!(decoded.len(), bgra.len());
+            for (a, b) in decoded.iter().zip(&bgra) {
+                assert!(a.abs_diff(*b) <= 8, "{:?}: {} vs {}", format, a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use bevy::render::render_resource::TextureFormat;
+use gv_video::GVFormat;
+
+use crate::blankable_image_data_provider::BGRAImageFrameProvider;
+pub use crate::gv::get_frame_bytes;
+pub use crate::gv_bc::{compress_bgra, pad_bgra_to_multiple_of_4};
+use crate::gv::HEADER_SIZE;
+use crate::movie_player::MoviePlayer;
+use crate::thumbnail::FrameGrabber;
+
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn get_format_id(format: GVFormat) -> u32 {
+    match format {
+        GVFormat::DXT1 => 1,
+        GVFormat::DXT3 => 3,
+        GVFormat::DXT5 => 5,
+        GVFormat::BC7 => 7,
+    }
+}
+
+/// Parse `dxt1` / `dxt3` / `dxt5` / `bc7` (also `bc1` / `bc2` / `bc3`)
+pub fn parse_gv_format(name: &str) -> Option<GVFormat> {
+    match name.to_ascii_lowercase().as_str() {
+        "dxt1" | "bc1" => Some(GVFormat::DXT1),
+        "dxt3" | "bc2" => Some(GVFormat::DXT3),
+        "dxt5" | "bc3" => Some(GVFormat::DXT5),
+        "bc7" => Some(GVFormat::BC7),
+        _ => None,
+    }
+}
+
+
+/// Writes `.gv` file: header, LZ4 compressed BCn frames, and address/size table at the end.
+///
+/// ```ignore
+/// let mut encoder = GVEncoder::new(File::create("out.gv")?, 256, 256, 30.0, GVFormat::DXT5)?;
+/// encoder.add_frame_bgra(&bgra)?;
+/// encoder.finish()?;
+/// ```
+pub struct GVEncoder<W: Write + Seek> {
+    writer: W,
+    width: u32,
+    height: u32,
+    fps: f32,
+    format: GVFormat,
+    /// (address, size) of each frame
+    address_size_blocks: Vec<(u64, u64)>,
+    position: u64,
+}
+
+impl<W: Write + Seek> GVEncoder<W> {
+    /// width and height must be multiple of 4 (see `pad_bgra_to_multiple_of_4`)
+    pub fn new(mut writer: W, width: u32, height: u32, fps: f32, format: GVFormat) -> std::io::Result<Self> {
+        if width == 0 || height == 0 || width % 4 != 0 || height % 4 != 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("GV resolution must be multiple of 4: {}x{}", width, height),
+            ));
+        }
+        // frame count is written on finish
+        writer.write_all(&Self::header_bytes(width, height, 0, fps, format))?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            fps,
+            format,
+            address_size_blocks: vec![],
+            position: HEADER_SIZE,
+        })
+    }
+
+    fn header_bytes(width: u32, height: u32, frame_count: u32, fps: f32, format: GVFormat) -> Vec<u8> {
+        let frame_bytes = get_frame_bytes(width, height, format) as u32;
+        [width, height, frame_count, fps.to_bits(), get_format_id(format), frame_bytes]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect()
+    }
+
+    pub fn get_resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        self.address_size_blocks.len() as u32
+    }
+
+    /// Add a frame of BGRA8 (straight alpha), `width * height * 4` bytes
+    pub fn add_frame_bgra(&mut self, bgra: &[u8]) -> std::io::Result<()> {
+        if bgra.len() != (self.width * self.height * 4) as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("frame must be {}x{} BGRA", self.width, self.height),
+            ));
+        }
+        let blocks = compress_bgra(bgra, self.width, self.height, self.format);
+        self.add_frame_compressed(&blocks)
+    }
+
+    /// Add a frame of already compressed BCn blocks
+    pub fn add_frame_compressed(&mut self, blocks: &[u8]) -> std::io::Result<()> {
+        let compressed = lz4_flex::block::compress(blocks);
+        self.writer.write_all(&compressed)?;
+        self.address_size_blocks.push((self.position, compressed.len() as u64));
+        self.position += compressed.len() as u64;
+        Ok(())
+    }
+
+    /// Write the address/size table and the frame count, and return the writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        for (address, size) in &self.address_size_blocks {
+            self.writer.write_all(&address.to_le_bytes())?;
+            self.writer.write_all(&size.to_le_bytes())?;
+        }
+        let header = Self::header_bytes(self.width, self.height, self.get_frame_count(), self.fps, self.format);
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&header)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GVEncodeSettings {
+    /// `None` = frame rate of the source (or 30fps for image sequence)
+    pub fps: Option<f32>,
+    /// use `DXT5` or `BC7` for smooth alpha, `DXT1` has 1-bit alpha
+    pub format: GVFormat,
+}
+
+impl Default for GVEncodeSettings {
+    fn default() -> Self {
+        Self {
+            fps: None,
+            format: GVFormat::DXT5,
+        }
+    }
+}
+
+fn invalid_input<E: ToString>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+}
+
+/// Encode all frames of a movie player (e.g. `FFmpegMoviePlayer`, `LottieMoviePlayer`) into `.gv`.
+///
+/// Frames are read by `FrameGrabber::get_frame_at`, so the playing state of the player is kept.
+/// Resolution is padded to multiple of 4 with transparent pixels.
+/// `on_progress` is called with (done, total) frames.
+pub fn encode_movie_to_gv<P, W, F>(
+    player: &mut P,
+    writer: W,
+    settings: &GVEncodeSettings,
+    mut on_progress: F,
+) -> std::io::Result<W>
+    where
+        P: MoviePlayer + BGRAImageFrameProvider,
+        W: Write + Seek,
+        F: FnMut(u32, u32),
+{
+    if player.is_live() {
+        return Err(invalid_input("live stream can't be encoded"));
+    }
+    if player.get_frame_texture_format() != TextureFormat::Bgra8UnormSrgb {
+        return Err(invalid_input(format!("frames must be BGRA8, not {:?}", player.get_frame_texture_format())));
+    }
+    let fps = settings.fps.unwrap_or_else(|| player.get_metadata().frame_rate.unwrap_or(30.0));
+    let frame_count = ((player.get_duration().as_secs_f64() * fps as f64).round() as u32).max(1);
+    let (width, height) = player.get_resolution();
+
+    let mut encoder = GVEncoder::new(writer, width.div_ceil(4) * 4, height.div_ceil(4) * 4, fps, settings.format)?;
+    for i in 0..frame_count {
+        let t = Duration::from_secs_f64(i as f64 / fps as f64);
+        // straight alpha
+        let Some(frame) = player.get_frame_at(t).filter(|frame| frame.resolution == (width, height)) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("failed to read frame at {:?}", t),
+            ));
+        };
+        let (frame, ..) = pad_bgra_to_multiple_of_4(&frame.data, width, height);
+        encoder.add_frame_bgra(&frame)?;
+        on_progress(i + 1, frame_count);
+    }
+    encoder.finish()
+}
+
+/// Image files in the directory which can be decoded (by extension), sorted by file name.
+///
+/// NOTE: sorted as strings, so use zero padded numbers (`frame_0001.png`)
+pub fn list_image_sequence<P: AsRef<Path>>(dir: P) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| image::ImageFormat::from_path(path).is_ok_and(|format| format.reader_enabled()))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Load an image file as BGRA8, returns (data, width, height)
+pub fn load_image_bgra<P: AsRef<Path>>(path: P) -> std::io::Result<(Vec<u8>, u32, u32)> {
+    let image = image::open(path.as_ref())
+        .map_err(|e| invalid_input(format!("{}: {}", path.as_ref().display(), e)))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    let mut data = image.into_raw();
+    for px in data.chunks_exact_mut(4) {
+        px.swap(0, 2);
+    }
+    Ok((data, width, height))
+}
+
+/// Encode image sequence (straight alpha, all the same size) into `.gv`.
+///
+/// PNG is supported by default, other formats follow the enabled features of `image` crate.
+pub fn encode_images_to_gv<P, W, F>(
+    paths: &[P],
+    writer: W,
+    settings: &GVEncodeSettings,
+    mut on_progress: F,
+) -> std::io::Result<W>
+    where
+        P: AsRef<Path>,
+        W: Write + Seek,
+        F: FnMut(u32, u32),
+{
+    let Some(first) = paths.first() else {
+        return Err(invalid_input("no images to encode"));
+    };
+    let fps = settings.fps.unwrap_or(30.0);
+    let frame_count = paths.len() as u32;
+    let (_, width, height) = load_image_bgra(first)?;
+
+    let mut encoder = GVEncoder::new(writer, width.div_ceil(4) * 4, height.div_ceil(4) * 4, fps, settings.format)?;
+    for (i, path) in paths.iter().enumerate() {
+        let (frame, frame_width, frame_height) = load_image_bgra(path)?;
+        if (frame_width, frame_height) != (width, height) {
+            return Err(invalid_input(format!(
+                "{}: {}x{} differs from {}x{}",
+                path.as_ref().display(), frame_width, frame_height, width, height,
+            )));
+        }
+        let (frame, ..) = pad_bgra_to_multiple_of_4(&frame, width, height);
+        encoder.add_frame_bgra(&frame)?;
+        on_progress(i as u32 + 1, frame_count);
+    }
+    encoder.finish()
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gv_video::GVVideo;
+    use std::io::Cursor;
+
+    fn gradient(width: u32, height: u32) -> Vec<u8> {
+        (0..width * height)
+            .flat_map(|i| {
+                let (x, y) = (i % width, i / width);
+                [(x * 255 / width) as u8, (y * 255 / height) as u8, 128, if x < width / 2 { 255 } else { 0 }]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        for format in [GVFormat::DXT1, GVFormat::DXT3, GVFormat::DXT5, GVFormat::BC7] {
+            let frame = gradient(16, 8);
+            let mut encoder = GVEncoder::new(Cursor::new(Vec::new()), 16, 8, 24.0, format).unwrap();
+            encoder.add_frame_bgra(&frame).unwrap();
+            encoder.add_frame_bgra(&frame).unwrap();
+            let bytes = encoder.finish().unwrap().into_inner();
+
+            let mut gv = GVVideo::load(Cursor::new(bytes));
+            assert_eq!(gv.get_resolution(), (16, 8));
+            assert_eq!(gv.get_frame_count(), 2);
+            assert_eq!(gv.get_fps(), 24.0);
+
+            let decoded = gv_video::get_bgra_vec_from_frame(gv.read_frame(1).unwrap());
+            // opaque / transparent halves are kept
+            assert_eq!(decoded[3], 255, "{:?}", format);
+            assert_eq!(decoded[15 * 4 + 3], 0, "{:?}", format);
+            // colors are roughly kept
+            let error = frame.chunks_exact(4).zip(decoded.chunks_exact(4))
+                .filter(|(src, _)| src[3] == 255)
+                .map(|(src, dst)| (0..3).map(|i| (src[i] as i32 - dst[i] as i32).abs()).max().unwrap())
+                .max()
+                .unwrap();
+            assert!(error < 48, "{:?}: {}", format, error);
+        }
+    }
+
+    #[test]
+    fn invalid_resolution() {
+        assert!(GVEncoder::new(Cursor::new(Vec::new()), 10, 8, 30.0, GVFormat::DXT1).is_err());
+        let (padded, width, height) = pad_bgra_to_multiple_of_4(&[255; 5 * 3 * 4], 5, 3);
+        assert_eq!((width, height), (8, 4));
+        assert_eq!(padded.len(), 8 * 4 * 4);
+        assert_eq!(&padded[5 * 4..5 * 4 + 4], &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn encode_images() {
+        let dir = std::env::temp_dir().join("bevy_movie_player_encode_images");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..3u8 {
+            let image = image::RgbaImage::from_pixel(6, 5, image::Rgba([255, i * 100, 0, 255]));
+            image.save(dir.join(format!("frame_{:04}.png", i))).unwrap();
+        }
+        std::fs::write(dir.join("readme.txt"), "not an image").unwrap();
+
+        let paths = list_image_sequence(&dir).unwrap();
+        assert_eq!(paths.len(), 3);
+        let settings = GVEncodeSettings {
+            fps: Some(12.0),
+            format: GVFormat::DXT1,
+        };
+        let bytes = encode_images_to_gv(&paths, Cursor::new(Vec::new()), &settings, |_, _| {})
+            .unwrap()
+            .into_inner();
+
+        let mut gv = GVVideo::load(Cursor::new(bytes));
+        assert_eq!(gv.get_resolution(), (8, 8));
+        assert_eq!(gv.get_frame_count(), 3);
+        assert_eq!(gv.get_fps(), 12.0);
+        let frame = gv_video::get_bgra_vec_from_frame(gv.read_frame(2).unwrap());
+        // BGRA: red = 255, green = 200
+        assert!(frame[2] > 240 && (frame[1] as i32 - 200).abs() < 16);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,87 @@
+/// Size of GV header (width, height, frame count, fps, format, frame bytes)
+pub(crate) const HEADER_SIZE: u64 = 24;
+
+/// GV header, shared by format detection and the integrity check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct GVHeader {
+    pub width: u32,
+    pub height: u32,
+    pub frame_count: u32,
+    pub fps: f32,
+    /// 1: DXT1, 3: DXT3, 5: DXT5, 7: BC7
+    pub format_id: u32,
+    pub frame_bytes: u32,
+}
+
+/// Bytes of a BCn block by GV format id, `None` if unknown
+pub(crate) fn get_block_bytes_from_format_id(format_id: u32) -> Option<u64> {
+    match format_id {
+        // BC1 (DXT1) blocks are 8 bytes, others 16 bytes
+        1 => Some(8),
+        3 | 5 | 7 => Some(16),
+        _ => None,
+    }
+}
+
+impl GVHeader {
+    /// Read header fields (little endian). `None` if `bytes` is shorter than the header
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_SIZE as usize {
+            return None;
+        }
+        let field = |i: usize| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        Some(Self {
+            width: field(0),
+            height: field(1),
+            frame_count: field(2),
+            fps: f32::from_bits(field(3)),
+            format_id: field(4),
+            frame_bytes: field(5),
+        })
+    }
+
+    /// Check fields, returns why the header is invalid
+    pub fn check(&self) -> Result<(), String> {
+        let (width, height) = (self.width, self.height);
+        if self.frame_count == 0 {
+            return Err("GV has no frames".to_string());
+        }
+        if width == 0 || height == 0 || width % 4 != 0 || height % 4 != 0 {
+            return Err(format!("invalid GV resolution: {}x{}", width, height));
+        }
+        if !self.fps.is_finite() || self.fps <= 0.0 {
+            return Err(format!("invalid GV fps: {}", self.fps));
+        }
+        let block_bytes = get_block_bytes_from_format_id(self.format_id)
+            .ok_or_else(|| format!("unknown GV format: {}", self.format_id))?;
+        let expected = (width as u64 / 4) * (height as u64 / 4) * block_bytes;
+        if self.frame_bytes as u64 != expected {
+            return Err(format!(
+                "GV frame bytes {} doesn't match {}x{} (format {}), expected {}",
+                self.frame_bytes, width, height, self.format_id, expected,
+            ));
+        }
+        Ok(())
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_header() {
+        let bytes = std::fs::read("assets/test.gv").unwrap();
+        let header = GVHeader::parse(&bytes).unwrap();
+        assert_eq!((header.width, header.height, header.frame_count, header.format_id), (640, 360, 1, 1));
+        assert_eq!(header.fps, 30.0);
+        assert!(header.check().is_ok());
+
+        assert!(GVHeader::parse(&bytes[..10]).is_none());
+        assert!(GVHeader { frame_count: 0, ..header }.check().is_err());
+        assert!(GVHeader { format_id: 2, ..header }.check().is_err());
+        assert!(GVHeader { frame_bytes: header.frame_bytes * 2, format_id: 5, ..header }.check().is_ok());
+        assert!(GVHeader { width: 642, ..header }.check().is_err());
+    }
+}
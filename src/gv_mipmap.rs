@@ -0,0 +1,206 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::image::Image;
+use bevy::image::ImageSampler;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use gv_video::GVFormat;
+
+use crate::gv::get_frame_bytes;
+use crate::gv::get_texture_format_from_gv_format;
+use crate::gv::GVMoviePlayer;
+use crate::gv_bc::compress_bgra;
+use crate::gv_bc::decompress_bgra;
+use crate::gv_bc::pad_bgra_to_multiple_of_4;
+use crate::image_data_provider::CompressedImageDataProvider;
+use crate::movie_player::ImageData;
+use crate::movie_player::MoviePlayer;
+
+use std::io::Read;
+use std::io::Seek;
+
+/// Number of mip levels down to 1x1
+pub fn get_mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Size of mip `level`
+pub fn get_mip_size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+/// Bytes of BCn mip chain (`levels` including the base), each level rounded up to 4x4 blocks
+pub fn get_mip_chain_bytes(width: u32, height: u32, format: GVFormat, levels: u32) -> usize {
+    (0..levels)
+        .map(|level| {
+            let (w, h) = get_mip_size(width, height, level);
+            get_frame_bytes(w.div_ceil(4) * 4, h.div_ceil(4) * 4, format)
+        })
+        .sum()
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let s = if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (s * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// Half size BGRA8 (sRGB, straight alpha) by 2x2 box filter. Colors are averaged in linear space
+/// and weighted by alpha, so transparent pixels don't darken edges.
+pub fn downsample_bgra(bgra: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    let (new_width, new_height) = get_mip_size(width, height, 1);
+    let (w, h) = (width as usize, height as usize);
+    let to_linear: Vec<f32> = (0..=255).map(srgb_to_linear).collect();
+    let mut downsampled = Vec::with_capacity(new_width as usize * new_height as usize * 4);
+    for y in 0..new_height as usize {
+        for x in 0..new_width as usize {
+            let mut color = [0f32; 3];
+            let mut alpha = 0u32;
+            for (sx, sy) in [(x * 2, y * 2), (x * 2 + 1, y * 2), (x * 2, y * 2 + 1), (x * 2 + 1, y * 2 + 1)] {
+                // clamp to edge for odd (or 1px) sizes
+                let p = &bgra[(sy.min(h - 1) * w + sx.min(w - 1)) * 4..][..4];
+                for (sum, &value) in color.iter_mut().zip(p) {
+                    *sum += to_linear[value as usize] * p[3] as f32;
+                }
+                alpha += p[3] as u32;
+            }
+            for c in color {
+                downsampled.push(if alpha > 0 { linear_to_srgb(c / alpha as f32) } else { 0 });
+            }
+            downsampled.push(((alpha + 2) / 4) as u8);
+        }
+    }
+    (downsampled, new_width, new_height)
+}
+
+/// Compress mip levels `1..levels` of a decoded frame (the base level is not included)
+pub fn compress_mip_chain(bgra: &[u8], width: u32, height: u32, format: GVFormat, levels: u32) -> Vec<u8> {
+    let mut data = vec![];
+    let (mut level_bgra, mut level_width, mut level_height) = (bgra.to_vec(), width, height);
+    for _ in 1..levels {
+        (level_bgra, level_width, level_height) = downsample_bgra(&level_bgra, level_width, level_height);
+        let (padded, padded_width, padded_height) = pad_bgra_to_multiple_of_4(&level_bgra, level_width, level_height);
+        data.extend(compress_bgra(&padded, padded_width, padded_height, format));
+    }
+    data
+}
+
+/// Mipmapped playback of the compressed path: the GV frame is used as the base level as is,
+/// and lower levels are generated on the fly (decoded, downsampled and compressed again),
+/// so videos shown small or at an angle in 3D don't shimmer.
+///
+/// NOTE: costs one BCn decode of the read frame + 1/3 frame of encoding per new frame (skipped while the frame doesn't change)
+impl<R: Read + Seek> GVMoviePlayer<R> {
+    /// Create a compressed image with the mip chain of the current frame, and a linear (trilinear) sampler
+    pub fn create_mipmapped_image(&mut self) -> Image {
+        let (width, height) = self.get_resolution();
+        let mut image = Image::new_uninit(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            get_texture_format_from_gv_format(self.gv.get_format()),
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::linear();
+        self.set_mipmapped_image_data(&mut image);
+        image
+    }
+
+    /// Set the current frame with its mip chain (same as `set_compressed_image_data`, with mips).
+    /// Blank frames (1x1) have no mips.
+    pub fn set_mipmapped_image_data(&mut self, image: &mut Image) {
+        let base = self.get_compressed_image_data();
+        let (width, height) = self.get_resolution();
+        let format = self.gv.get_format();
+        let levels = get_mip_level_count(width, height);
+        let chain_bytes = get_mip_chain_bytes(width, height, format, levels);
+
+        if base.resolution != (width, height) {
+            set_image(image, base, 1);
+            return;
+        }
+        // same frame (e.g. paused): keep generated mips
+        let unchanged = image.texture_descriptor.mip_level_count == levels
+            && image.texture_descriptor.format == base.format
+            && image.data.as_ref().is_some_and(|data| data.len() == chain_bytes && data.starts_with(&base.data));
+        if unchanged {
+            return;
+        }
+
+        // decode the frame already read instead of reading it again
+        let Ok(decoded) = decompress_bgra(&base.data, width, height, format) else {
+            set_image(image, base, 1);
+            return;
+        };
+        let mut data = base.data;
+        data.extend(compress_mip_chain(&decoded, width, height, format, levels));
+        set_image(image, ImageData { data, ..base }, levels);
+    }
+}
+
+fn set_image(image: &mut Image, image_data: ImageData, mip_level_count: u32) {
+    image.data = Some(image_data.data);
+    image.texture_descriptor.format = image_data.format;
+    image.texture_descriptor.size = Extent3d {
+        width: image_data.resolution.0,
+        height: image_data.resolution.1,
+        depth_or_array_layers: 1,
+    };
+    image.texture_descriptor.mip_level_count = mip_level_count;
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blankable_image_data_provider::CompressedImageFrameProvider;
+    use crate::gv::load_gv_on_memory;
+
+    #[test]
+    fn mip_levels() {
+        assert_eq!(get_mip_level_count(1, 1), 1);
+        assert_eq!(get_mip_level_count(4, 4), 3);
+        assert_eq!(get_mip_level_count(640, 360), 10);
+        assert_eq!(get_mip_size(640, 360, 9), (1, 1));
+        // 8x4, 4x2, 2x1, 1x1: one block each except the base
+        assert_eq!(get_mip_chain_bytes(8, 4, GVFormat::DXT1, 4), 16 + 8 * 3);
+    }
+
+    #[test]
+    fn downsample() {
+        // 2x1: opaque red and transparent pixel, 1x1 keeps red color with half alpha
+        let bgra = [0, 0, 255, 255, 0, 0, 0, 0];
+        let (downsampled, width, height) = downsample_bgra(&bgra, 2, 1);
+        assert_eq!((width, height), (1, 1));
+        assert_eq!(downsampled, vec![0, 0, 255, 128]);
+
+        // black and white average to linear 0.5, not sRGB 128
+        let bgra = [0, 0, 0, 255, 255, 255, 255, 255];
+        let (downsampled, ..) = downsample_bgra(&bgra, 2, 1);
+        assert_eq!(downsampled, vec![188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn mipmapped_image() {
+        let mut player = load_gv_on_memory("assets/test.gv");
+        player.play();
+        let (width, height) = player.get_resolution();
+        let levels = get_mip_level_count(width, height);
+
+        let mut image = player.create_mipmapped_image();
+        assert_eq!(image.texture_descriptor.mip_level_count, levels);
+        let data = image.data.clone().unwrap();
+        assert_eq!(data.len(), get_mip_chain_bytes(width, height, player.gv.get_format(), levels));
+        assert!(data.starts_with(&player.get_playing_frame_compressed().unwrap()));
+
+        // unchanged frame keeps the data
+        player.set_mipmapped_image_data(&mut image);
+        assert_eq!(image.data.unwrap(), data);
+    }
+}
@@ -0,0 +1,373 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::image::Image;
+use bevy::prelude::*;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureViewDescriptor;
+use bevy::render::render_resource::TextureViewDimension;
+use gv_video::GVFormat;
+
+use crate::blankable_image_data_provider::BlankMode;
+use crate::blankable_image_data_provider::Blankable;
+use crate::blankable_image_data_provider::CompressedImageDataProvider;
+use crate::gv::get_frame_bytes;
+use crate::gv::get_texture_format_from_gv_format;
+use crate::gv::GVMoviePlayer;
+use crate::gv::GVReader;
+use crate::movie_player::MoviePlayer;
+use crate::movie_player::PlayingState;
+
+use std::io::Read;
+use std::io::Seek;
+use std::time::Duration;
+
+/// Packs compressed frames of multiple GV players into layers of one 2D texture array,
+/// so many small clips can be drawn in one draw call (e.g. a custom material with `texture_2d_array`,
+/// indexed by the layer of each player, see `GVTextureArrayMaterial` with `gv-material` feature).
+///
+/// All players must have the same resolution and `GVFormat`. Blank layers follow the `BlankMode` of each player
+/// (black, white or transparent).
+pub struct GVTextureArray<R: Read + Seek> {
+    resolution: (u32, u32),
+    format: GVFormat,
+    /// player of each layer
+    players: Vec<GVMoviePlayer<R>>,
+    /// frame written to the image of each layer, `None` to write it again
+    written: Vec<Option<LayerKey>>,
+}
+
+/// (state, blank mode, frame) of a layer, which decides what is shown
+type LayerKey = (PlayingState, BlankMode, Option<u32>);
+
+/// `None` while the shown frame is not read yet (the held frame is written until it is)
+fn get_layer_key<R: Read + Seek>(player: &GVMoviePlayer<R>) -> Option<LayerKey> {
+    let frame = player.get_shown_frame_index();
+    if frame.is_some_and(|frame| !player.is_frame_ready(frame)) {
+        return None;
+    }
+    Some((player.get_state(), player.get_blank_mode(), frame))
+}
+
+/// Transparent BCn block
+fn transparent_block(format: GVFormat) -> &'static [u8] {
+    match format {
+        // 3 color mode (color0 <= color1), index 3 = transparent
+        GVFormat::DXT1 => &[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF],
+        // alpha 0
+        GVFormat::DXT3 | GVFormat::DXT5 => &[0; 16],
+        // mode 6, all endpoints 0
+        GVFormat::BC7 => &[0x40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    }
+}
+
+/// BCn block filled with one BGRA color (opaque or transparent)
+fn solid_block(format: GVFormat, bgra: [u8; 4]) -> Vec<u8> {
+    if bgra[3] == 0 {
+        return transparent_block(format).to_vec();
+    }
+    let [b, g, r, _] = bgra;
+    let rgb565 = ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3);
+    // color0 == color1, all indices 0
+    let [lo, hi] = rgb565.to_le_bytes();
+    let color = [lo, hi, lo, hi, 0, 0, 0, 0];
+    match format {
+        GVFormat::DXT1 => color.to_vec(),
+        // explicit alpha 0xF
+        GVFormat::DXT3 => [[0xFF; 8], color].concat(),
+        // alpha0 == alpha1 == 255, all indices 0
+        GVFormat::DXT5 => [[0xFF, 0xFF, 0, 0, 0, 0, 0, 0], color].concat(),
+        // mode 6: 7 bit endpoints + p bit 1 (opaque alpha, color | 1), all indices 0
+        GVFormat::BC7 => {
+            let mut bits: u128 = 1 << 6;
+            let mut offset = 7;
+            for c in [r, r, g, g, b, b, 0xFF, 0xFF] {
+                bits |= ((c >> 1) as u128) << offset;
+                offset += 7;
+            }
+            bits |= 0b11 << offset;
+            bits.to_le_bytes().to_vec()
+        },
+    }
+}
+
+impl<R: Read + Seek> GVTextureArray<R> {
+    pub fn new(resolution: (u32, u32), format: GVFormat) -> Self {
+        Self {
+            resolution,
+            format,
+            players: vec![],
+            written: vec![],
+        }
+    }
+
+    /// Add a player as a new layer, returns the layer index
+    pub fn push(&mut self, player: GVMoviePlayer<R>) -> Result<u32, std::io::Error> {
+        let resolution = player.get_resolution();
+        let format = player.gv.get_format();
+        if resolution != self.resolution || get_texture_format_from_gv_format(format) != get_texture_format_from_gv_format(self.format) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "GV texture array is {}x{} {:?}, but the player is {}x{} {:?}",
+                    self.resolution.0, self.resolution.1, self.format, resolution.0, resolution.1, format,
+                ),
+            ));
+        }
+        self.players.push(player);
+        self.written.push(None);
+        Ok(self.players.len() as u32 - 1)
+    }
+
+    pub fn get_layer_count(&self) -> u32 {
+        self.players.len() as u32
+    }
+
+    pub fn get_resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    pub fn get_format(&self) -> GVFormat {
+        self.format
+    }
+
+    pub fn get_player(&self, layer: u32) -> Option<&GVMoviePlayer<R>> {
+        self.players.get(layer as usize)
+    }
+
+    pub fn get_player_mut(&mut self, layer: u32) -> Option<&mut GVMoviePlayer<R>> {
+        self.players.get_mut(layer as usize)
+    }
+
+    pub fn players_mut(&mut self) -> impl Iterator<Item = &mut GVMoviePlayer<R>> {
+        self.players.iter_mut()
+    }
+
+    /// Update all players (same as `MoviePlayer::update`)
+    pub fn update(&mut self, bevy_elapsed_time: Duration) {
+        for player in &mut self.players {
+            player.update(bevy_elapsed_time);
+        }
+    }
+
+    /// true if any layer shows another frame than the one written to the image
+    pub fn has_changed_layers(&self) -> bool {
+        self.players.iter().zip(&self.written).any(|(player, written)| {
+            let key = get_layer_key(player);
+            key.is_none() || key != *written
+        })
+    }
+
+    /// Compressed data of a layer, and marks it written
+    fn get_layer_data(&mut self, layer: usize) -> Vec<u8> {
+        let frame_bytes = get_frame_bytes(self.resolution.0, self.resolution.1, self.format);
+        let player = &mut self.players[layer];
+        self.written[layer] = get_layer_key(player);
+        // same as a single player: frame, or 1x1 BGRA blank by `BlankMode`
+        let frame = player.get_compressed_image_data();
+        if frame.resolution == self.resolution && frame.data.len() == frame_bytes {
+            frame.data
+        } else {
+            let bgra = match frame.data[..] {
+                [b, g, r, a, ..] if frame.resolution == (1, 1) => [b, g, r, a],
+                _ => [0; 4],
+            };
+            let block = solid_block(self.format, bgra);
+            block.iter().cycle().take(frame_bytes).copied().collect()
+        }
+    }
+
+    /// Compressed data of all layers (layer major)
+    fn get_data(&mut self) -> Vec<u8> {
+        (0..self.players.len()).flat_map(|layer| self.get_layer_data(layer)).collect()
+    }
+
+    /// Create a 2D array image with current frames of all layers.
+    /// Recreate it when layers are added. Layers are tracked for the last created image only.
+    pub fn create_image(&mut self) -> Image {
+        let data = self.get_data();
+        let mut image = Image::new(
+            Extent3d {
+                width: self.resolution.0,
+                height: self.resolution.1,
+                depth_or_array_layers: self.get_layer_count().max(1),
+            },
+            TextureDimension::D2,
+            data,
+            get_texture_format_from_gv_format(self.format),
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+        // NOTE: single layer is viewed as D2 by default
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        image
+    }
+
+    /// Update the image (created by `create_image`) with current frames, in one upload.
+    /// Only layers whose frame changed are read and written.
+    pub fn set_image_data(&mut self, image: &mut Image) {
+        let frame_bytes = get_frame_bytes(self.resolution.0, self.resolution.1, self.format);
+        let layer_count = self.players.len();
+        let matches = image.texture_descriptor.size.depth_or_array_layers == self.get_layer_count().max(1)
+            && image.data.as_ref().is_some_and(|data| data.len() == frame_bytes * layer_count);
+        if !matches {
+            *image = self.create_image();
+            return;
+        }
+        let data = image.data.as_mut().unwrap();
+        for layer in 0..layer_count {
+            let key = get_layer_key(&self.players[layer]);
+            if key.is_some() && key == self.written[layer] {
+                continue;
+            }
+            let layer_data = self.get_layer_data(layer);
+            data[layer * frame_bytes..][..frame_bytes].copy_from_slice(&layer_data);
+        }
+    }
+}
+
+/// Texture array of GV players, updated by `MoviePlayerPlugin` on frames any layer changes
+///
+/// ```ignore
+/// let image = images.add(array.create_image());
+/// commands.spawn(GVTextureArrayImage { array, image: image.clone() });
+/// ```
+#[derive(Component)]
+pub struct GVTextureArrayImage {
+    pub array: GVTextureArray<GVReader>,
+    pub image: Handle<Image>,
+}
+
+pub fn update_gv_texture_array_images(
+    time: Res<Time>,
+    mut images: ResMut<Assets<Image>>,
+    mut query: Query<&mut GVTextureArrayImage>,
+) {
+    for mut array_image in &mut query {
+        let array_image = &mut *array_image;
+        array_image.array.update(time.elapsed());
+        // not to upload the whole array while no layer changes (e.g. paused)
+        if !array_image.array.has_changed_layers() {
+            continue;
+        }
+        if let Some(image) = images.get_mut(&array_image.image) {
+            array_image.array.set_image_data(image);
+        }
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blankable_image_data_provider::CompressedImageFrameProvider;
+    use crate::gv::load_gv;
+    use crate::gv::load_gv_on_memory;
+
+    #[test]
+    fn layers() {
+        let first = load_gv_on_memory("assets/test.gv");
+        let resolution = first.get_resolution();
+        let format = first.gv.get_format();
+        let frame_bytes = get_frame_bytes(resolution.0, resolution.1, format);
+
+        let mut array = GVTextureArray::new(resolution, format);
+        assert_eq!(array.push(first).unwrap(), 0);
+        assert_eq!(array.push(load_gv_on_memory("assets/test.gv")).unwrap(), 1);
+        array.get_player_mut(0).unwrap().set_blank_mode(BlankMode::Transparent);
+
+        let image = array.create_image();
+        assert_eq!(image.texture_descriptor.size.depth_or_array_layers, 2);
+        let data = image.data.unwrap();
+        assert_eq!(data.len(), frame_bytes * 2);
+        // stopped: transparent, and the first frame by (default) blank mode
+        assert_eq!(&data[..8], &transparent_block(format)[..8]);
+        let first_frame = array.get_player_mut(1).unwrap().get_first_frame_compressed().unwrap();
+        assert_eq!(&data[frame_bytes..], &first_frame[..]);
+    }
+
+    #[test]
+    fn blank_modes() {
+        let player = load_gv_on_memory("assets/test.gv");
+        let resolution = player.get_resolution();
+        let format = player.gv.get_format();
+        let frame_bytes = get_frame_bytes(resolution.0, resolution.1, format);
+
+        let mut array = GVTextureArray::new(resolution, format);
+        for mode in [BlankMode::Black, BlankMode::White, BlankMode::LastFrameOnPause_TransparentOnStop] {
+            let mut player = load_gv_on_memory("assets/test.gv");
+            player.set_blank_mode(mode);
+            array.push(player).unwrap();
+        }
+
+        // stopped
+        let data = array.create_image().data.unwrap();
+        let black = solid_block(format, [0, 0, 0, 0xFF]);
+        let white = solid_block(format, [0xFF; 4]);
+        assert_ne!(black, transparent_block(format));
+        assert_eq!(&data[..black.len()], &black[..]);
+        assert_eq!(&data[frame_bytes..frame_bytes + white.len()], &white[..]);
+        assert_eq!(&data[frame_bytes * 2..frame_bytes * 2 + 8], &transparent_block(format)[..8]);
+
+        // paused: black / white stay blank, the paused frame otherwise
+        for player in array.players_mut() {
+            player.play();
+            player.pause();
+        }
+        let data = array.create_image().data.unwrap();
+        assert_eq!(&data[..black.len()], &black[..]);
+        let paused_frame = array.get_player_mut(2).unwrap().get_paused_frame_compressed().unwrap();
+        assert_eq!(&data[frame_bytes * 2..], &paused_frame[..]);
+    }
+
+    #[test]
+    fn changed_layers() {
+        let player = load_gv_on_memory("assets/test.gv");
+        let resolution = player.get_resolution();
+        let format = player.gv.get_format();
+        let frame_bytes = get_frame_bytes(resolution.0, resolution.1, format);
+
+        let mut array = GVTextureArray::new(resolution, format);
+        array.push(player).unwrap();
+        array.push(load_gv_on_memory("assets/test.gv")).unwrap();
+        let mut image = array.create_image();
+        assert!(!array.has_changed_layers());
+
+        // mark layer 1, then change layer 0 only
+        image.data.as_mut().unwrap()[frame_bytes..].fill(0xAA);
+        array.get_player_mut(0).unwrap().set_blank_mode(BlankMode::White);
+        assert!(array.has_changed_layers());
+        array.set_image_data(&mut image);
+        let data = image.data.unwrap();
+        let white = solid_block(format, [0xFF; 4]);
+        assert_eq!(&data[..white.len()], &white[..]);
+        assert!(data[frame_bytes..].iter().all(|&byte| byte == 0xAA));
+        assert!(!array.has_changed_layers());
+    }
+
+    #[test]
+    fn solid_blocks() {
+        for format in [GVFormat::DXT1, GVFormat::DXT3, GVFormat::DXT5, GVFormat::BC7] {
+            assert_eq!(solid_block(format, [0; 4]), transparent_block(format));
+            assert_eq!(solid_block(format, [0xFF; 4]).len(), transparent_block(format).len());
+        }
+        assert_eq!(solid_block(GVFormat::DXT1, [0xFF; 4]), [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]);
+        // mode 6, every endpoint 0x7F, p bits 1
+        let mut white = [0xFF; 16];
+        white[0] = 0xC0;
+        white[8] = 0x01;
+        for byte in &mut white[9..] {
+            *byte = 0;
+        }
+        assert_eq!(solid_block(GVFormat::BC7, [0xFF; 4]), white);
+    }
+
+    #[test]
+    fn mismatched_player() {
+        let player = load_gv("assets/test.gv");
+        let (width, height) = player.get_resolution();
+        let mut array = GVTextureArray::new((width + 4, height), player.gv.get_format());
+        assert!(array.push(player).is_err());
+    }
+}
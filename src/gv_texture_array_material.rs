@@ -0,0 +1,58 @@
+use bevy::asset::embedded_asset;
+use bevy::mesh::MeshTag;
+use bevy::prelude::*;
+use bevy::render::render_resource::AsBindGroup;
+use bevy::shader::ShaderRef;
+
+/// Unlit material drawing a layer of `GVTextureArray` image, so entities sharing one material
+/// (and mesh) are drawn in one draw call. The layer is chosen per entity by `GVTextureArrayLayer`.
+///
+/// ```ignore
+/// app.add_plugins(GVTextureArrayMaterialPlugin);
+///
+/// let material = materials.add(GVTextureArrayMaterial { array_texture: image.clone(), ..default() });
+/// for layer in 0..array.get_layer_count() {
+///     commands.spawn((Mesh3d(mesh.clone()), MeshMaterial3d(material.clone()), GVTextureArrayLayer(layer)));
+/// }
+/// ```
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct GVTextureArrayMaterial {
+    #[texture(0, dimension = "2d_array")]
+    #[sampler(1)]
+    pub array_texture: Handle<Image>,
+    pub alpha_mode: AlphaMode,
+}
+
+impl Material for GVTextureArrayMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_movie_player/shaders/gv_texture_array.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+}
+
+/// Layer of `GVTextureArray` drawn by `GVTextureArrayMaterial` (passed to the shader as `MeshTag`)
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[require(MeshTag)]
+pub struct GVTextureArrayLayer(pub u32);
+
+pub fn sync_gv_texture_array_layers(mut query: Query<(&GVTextureArrayLayer, &mut MeshTag), Changed<GVTextureArrayLayer>>) {
+    for (layer, mut tag) in &mut query {
+        tag.0 = layer.0;
+    }
+}
+
+/// Registers `GVTextureArrayMaterial` and its shader. Needs the renderer (`DefaultPlugins`).
+pub struct GVTextureArrayMaterialPlugin;
+
+impl Plugin for GVTextureArrayMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/gv_texture_array.wgsl");
+        app
+            .add_plugins(MaterialPlugin::<GVTextureArrayMaterial>::default())
+            .add_systems(PostUpdate, sync_gv_texture_array_layers)
+            ;
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::render::render_resource::TextureFormat;
+use half::f16;
+use serde::Deserialize;
+use serde::Serialize;
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Output texture format of decoded frames
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum FrameOutputFormat {
+    /// 8-bit sRGB (default)
+    #[default]
+    Bgra8,
+    /// linear light, 1.0 = SDR reference white. transfer (PQ/HLG) is decoded and tone mapping is applied.
+    Rgba16Float,
+    /// 10-bit, still encoded with the source transfer (e.g. PQ signal). decode it in your shader.
+    Rgb10a2Unorm,
+}
+
+impl FrameOutputFormat {
+    pub fn get_texture_format(&self) -> TextureFormat {
+        match self {
+            FrameOutputFormat::Bgra8 => TextureFormat::Bgra8UnormSrgb,
+            FrameOutputFormat::Rgba16Float => TextureFormat::Rgba16Float,
+            FrameOutputFormat::Rgb10a2Unorm => TextureFormat::Rgb10a2Unorm,
+        }
+    }
+
+    pub fn get_bytes_per_pixel(&self) -> usize {
+        match self {
+            FrameOutputFormat::Bgra8 => 4,
+            FrameOutputFormat::Rgba16Float => 8,
+            FrameOutputFormat::Rgb10a2Unorm => 4,
+        }
+    }
+
+    /// Convert a BGRA8 (sRGB) pixel into this format, e.g. for blank frames
+    pub fn pixel_from_bgra8(&self, bgra: &[u8]) -> Vec<u8> {
+        let rgba64 = [bgra[2], bgra[1], bgra[0], bgra[3]].map(|c| c as u16 * 257);
+        match self {
+            FrameOutputFormat::Bgra8 => bgra.to_vec(),
+            FrameOutputFormat::Rgba16Float => rgba64_to_rgba16_float(&rgba64, HdrTransfer::Sdr, &ToneMapping::None),
+            FrameOutputFormat::Rgb10a2Unorm => rgba64_to_rgb10a2(&rgba64),
+        }
+    }
+}
+
+/// Transfer characteristic of the source
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HdrTransfer {
+    /// BT.709 / BT.1886 / sRGB like SDR gamma
+    Sdr,
+    /// SMPTE ST 2084 (HDR10, Dolby Vision)
+    Pq,
+    /// ARIB STD-B67 (HLG)
+    Hlg,
+    Linear,
+}
+
+/// Nits of SDR reference white (ITU-R BT.2408)
+pub const SDR_REFERENCE_WHITE_NITS: f32 = 203.0;
+
+/// Tone mapping applied to linear light RGB (1.0 = SDR reference white) for `Rgba16Float` output.
+#[derive(Clone, Default)]
+pub enum ToneMapping {
+    /// keep HDR values (> 1.0), for HDR camera / post processing
+    #[default]
+    None,
+    /// extended Reinhard, `white` maps to 1.0
+    Reinhard { white: f32 },
+    /// custom hook
+    Custom(Arc<dyn Fn([f32; 3]) -> [f32; 3] + Send + Sync>),
+}
+
+impl fmt::Debug for ToneMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToneMapping::None => write!(f, "None"),
+            ToneMapping::Reinhard { white } => f.debug_struct("Reinhard").field("white", white).finish(),
+            ToneMapping::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl ToneMapping {
+    pub fn custom<F>(f: F) -> Self
+        where F: Fn([f32; 3]) -> [f32; 3] + Send + Sync + 'static
+    {
+        ToneMapping::Custom(Arc::new(f))
+    }
+
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        match self {
+            ToneMapping::None => rgb,
+            ToneMapping::Reinhard { white } => {
+                let w2 = white * white;
+                rgb.map(|c| c * (1.0 + c / w2) / (1.0 + c))
+            },
+            ToneMapping::Custom(f) => f(rgb),
+        }
+    }
+}
+
+/// PQ signal (0..1) to linear light (1.0 = 10000 nits)
+pub fn pq_eotf(e: f32) -> f32 {
+    const M1: f32 = 2610.0 / 16384.0;
+    const M2: f32 = 2523.0 / 4096.0 * 128.0;
+    const C1: f32 = 3424.0 / 4096.0;
+    const C2: f32 = 2413.0 / 4096.0 * 32.0;
+    const C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+    let ep = e.max(0.0).powf(1.0 / M2);
+    ((ep - C1).max(0.0) / (C2 - C3 * ep)).powf(1.0 / M1)
+}
+
+/// HLG signal (0..1) to scene linear light (0..1)
+pub fn hlg_inverse_oetf(e: f32) -> f32 {
+    const A: f32 = 0.17883277;
+    const B: f32 = 0.28466892;
+    const C: f32 = 0.559_910_7;
+
+    let e = e.max(0.0);
+    if e <= 0.5 {
+        e * e / 3.0
+    } else {
+        (((e - C) / A).exp() + B) / 12.0
+    }
+}
+
+/// Decode a signal to linear light where 1.0 = SDR reference white
+fn to_linear(rgb: [f32; 3], transfer: HdrTransfer) -> [f32; 3] {
+    match transfer {
+        HdrTransfer::Sdr => rgb.map(|c| c.max(0.0).powf(2.4)),
+        HdrTransfer::Linear => rgb,
+        HdrTransfer::Pq => rgb.map(|c| pq_eotf(c) * 10000.0 / SDR_REFERENCE_WHITE_NITS),
+        HdrTransfer::Hlg => {
+            // HLG OOTF for 1000 nits display (gamma 1.2), BT.2020 luminance
+            let scene = rgb.map(hlg_inverse_oetf);
+            let y = 0.2627 * scene[0] + 0.6780 * scene[1] + 0.0593 * scene[2];
+            let gain = if y > 0.0 { y.powf(0.2) } else { 0.0 };
+            scene.map(|c| c * gain * 1000.0 / SDR_REFERENCE_WHITE_NITS)
+        },
+    }
+}
+
+/// Convert tightly packed RGBA64 (16-bit per channel) into `Rgba16Float` bytes
+pub fn rgba64_to_rgba16_float(src: &[u16], transfer: HdrTransfer, tone_mapping: &ToneMapping) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(src.len() * 2);
+    for px in src.chunks_exact(4) {
+        let rgb = [px[0], px[1], px[2]].map(|c| c as f32 / 65535.0);
+        let rgb = tone_mapping.apply(to_linear(rgb, transfer));
+        let a = px[3] as f32 / 65535.0;
+        for c in [rgb[0], rgb[1], rgb[2], a] {
+            dst.extend_from_slice(&f16::from_f32(c).to_le_bytes());
+        }
+    }
+    dst
+}
+
+/// Convert tightly packed RGBA64 (16-bit per channel) into `Rgb10a2Unorm` bytes (signal is kept)
+pub fn rgba64_to_rgb10a2(src: &[u16]) -> Vec<u8> {
+    let mut dst = Vec::with_capacity(src.len());
+    for px in src.chunks_exact(4) {
+        let r = (px[0] >> 6) as u32;
+        let g = (px[1] >> 6) as u32;
+        let b = (px[2] >> 6) as u32;
+        let a = (px[3] >> 14) as u32;
+        let packed = r | (g << 10) | (b << 20) | (a << 30);
+        dst.extend_from_slice(&packed.to_le_bytes());
+    }
+    dst
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pq_reference_white() {
+        // PQ code value of 203 nits is about 0.58
+        let linear = to_linear([0.5806; 3], HdrTransfer::Pq);
+        assert!((linear[0] - 1.0).abs() < 0.01);
+        assert_eq!(pq_eotf(0.0), 0.0);
+        assert!((pq_eotf(1.0) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn convert_pixels() {
+        let src = [65535, 0, 32768, 65535];
+        let packed = rgba64_to_rgb10a2(&src);
+        let packed = u32::from_le_bytes([packed[0], packed[1], packed[2], packed[3]]);
+        assert_eq!(packed & 0x3FF, 1023);
+        assert_eq!((packed >> 10) & 0x3FF, 0);
+        assert_eq!((packed >> 20) & 0x3FF, 512);
+        assert_eq!(packed >> 30, 3);
+
+        let float = rgba64_to_rgba16_float(&src, HdrTransfer::Linear, &ToneMapping::None);
+        assert_eq!(float.len(), 8);
+        assert_eq!(f16::from_le_bytes([float[0], float[1]]).to_f32(), 1.0);
+
+        // PQ peak (10000 nits) is mapped to 1.0
+        let white = 10000.0 / SDR_REFERENCE_WHITE_NITS;
+        let tone_mapped = rgba64_to_rgba16_float(&src, HdrTransfer::Pq, &ToneMapping::Reinhard { white });
+        assert!((f16::from_le_bytes([tone_mapped[0], tone_mapped[1]]).to_f32() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn blank_pixels() {
+        let white = [255, 255, 255, 255];
+        assert_eq!(FrameOutputFormat::Bgra8.pixel_from_bgra8(&white), white.to_vec());
+        assert_eq!(FrameOutputFormat::Rgb10a2Unorm.pixel_from_bgra8(&white), u32::MAX.to_le_bytes().to_vec());
+        let float = FrameOutputFormat::Rgba16Float.pixel_from_bgra8(&[0, 0, 255, 0]);
+        assert_eq!(float.len(), 8);
+        assert_eq!(f16::from_le_bytes([float[0], float[1]]).to_f32(), 1.0);
+        assert_eq!(f16::from_le_bytes([float[6], float[7]]).to_f32(), 0.0);
+    }
+}
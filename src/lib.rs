@@ -2,17 +2,52 @@ pub mod movie_player;
 pub mod image_data_provider;
 pub mod blankable_image_data_provider;
 pub mod plugin;
+pub mod movie_format;
+pub mod subtitle;
+mod gv_header;
+pub mod thumbnail;
 
+#[cfg(feature = "ffmpeg")]
+pub mod hdr;
 #[cfg(feature = "ffmpeg")]
 pub mod ffmpeg;
+#[cfg(feature = "ffmpeg")]
+pub mod ffmpeg_stream;
+#[cfg(feature = "ffmpeg")]
+pub mod ffmpeg_hdr;
 #[cfg(feature = "gv")]
 pub mod gv;
+#[cfg(feature = "gv")]
+pub mod gv_asset_stream;
+#[cfg(feature = "gv")]
+pub mod gv_texture_array;
+#[cfg(feature = "gv-material")]
+pub mod gv_texture_array_material;
+#[cfg(feature = "gv")]
+pub mod gv_bc;
+#[cfg(feature = "gv-encode")]
+pub mod gv_encode;
+#[cfg(feature = "gv")]
+pub mod gv_mipmap;
 #[cfg(feature = "lottie")]
 pub mod lottie;
+#[cfg(feature = "lottie")]
+pub mod lottie_property;
+#[cfg(feature = "lottie-text")]
+pub mod lottie_text;
+#[cfg(feature = "dotlottie")]
+pub mod dotlottie;
+#[cfg(all(feature = "lottie", feature = "gv-encode"))]
+pub mod lottie_bake;
 
 pub mod prelude {
     pub use crate::plugin::MoviePlayerPlugin;
+    pub use crate::movie_format::{AnyMovie, MovieExtensions, MovieFormat};
+    pub use crate::subtitle::{MovieSubtitle, SubtitleCueChanged, SubtitleTrack};
     pub use crate::movie_player::MoviePlayer;
+    pub use crate::movie_player::MovieMetadata;
+    pub use crate::movie_player::LoadMode;
     pub use crate::movie_player::SeekOutOfBoundsError;
     pub use crate::image_data_provider::{ImageDataProvider, CompressedImageDataProvider, ImageCreator};
+    pub use crate::thumbnail::{FrameGrabber, ContactSheetSettings, create_contact_sheet};
 }
@@ -3,13 +3,27 @@ use bevy::asset::AssetLoader;
 use bevy::asset::AsyncReadExt;
 use bevy::asset::LoadContext;
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
 use bevy::tasks::ConditionalSendFuture;
+use bevy::tasks::TaskPool;
 use derivative::Derivative;
 use rlottie::Bgra;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::blankable_image_data_provider::BGRAImageFrameProvider;
 use crate::blankable_image_data_provider::BlankMode;
 use crate::blankable_image_data_provider::Blankable;
+use crate::lottie_property::apply_properties;
+use crate::lottie_property::LottieProperty;
+#[cfg(feature = "lottie-text")]
+use crate::lottie_text::convert_text_layers;
+#[cfg(feature = "lottie-text")]
+use crate::lottie_text::LottieFontMap;
+#[cfg(feature = "lottie-text")]
+use crate::lottie_text::LottieTextLayer;
+use crate::movie_format::default_lottie_extensions;
+use crate::movie_player::MovieMetadata;
 use crate::movie_player::MoviePlayerStateController;
 use crate::movie_player::MoviePlayer;
 
@@ -17,7 +31,12 @@ use rlottie::Animation as LottieAnimation;
 use rlottie::Surface as LottieSurface;
 
 use core::slice;
+use std::collections::HashMap;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::mem;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -28,11 +47,169 @@ pub struct LottieMoviePlayer {
     pub lottie: Arc<Mutex<LottieAnimation>>,
     #[derivative(Debug="ignore")]
     pub lottie_surface: LottieSurface,
+    pub info: LottieInfo,
+    /// original JSON, to rebuild the animation with property overrides / sub-frames
+    #[derivative(Debug="ignore")]
+    json_data: Vec<u8>,
+    /// `json_data` parsed on the first rebuild, reused by later ones
+    #[derivative(Debug="ignore")]
+    parsed_json: Option<serde_json::Value>,
+    resource_path: PathBuf,
+    overrides: Vec<(String, LottieProperty)>,
+    /// `(layer name, text)` replaced on text layers
+    #[cfg(feature = "lottie-text")]
+    texts: Vec<(String, String)>,
+    #[cfg(feature = "lottie-text")]
+    #[derivative(Debug="ignore")]
+    fonts: LottieFontMap,
+    needs_rebuild: bool,
+    unpremultiply_alpha: bool,
+    frame_interpolation: LottieFrameInterpolation,
+    /// sub-frames per authored frame (1 = stepped)
+    subframes: usize,
+    /// `fr` (including sub-frames), total frames, duration and intrinsic size,
+    /// cached not to lock `lottie` while the worker renders
+    frame_rate: f64,
+    total_frame: usize,
+    duration: Duration,
+    intrinsic_size: (u32, u32),
+    #[derivative(Debug="ignore")]
+    render_worker: Option<LottieRenderWorker>,
+    /// last frame rendered by the worker (front buffer)
+    #[derivative(Debug="ignore")]
+    front_frame: Option<RenderedFrame>,
+    /// incremented on rebuild, frames of older animations from the worker are dropped
+    generation: u64,
     #[derivative(Debug="ignore")]
     state_controller: MoviePlayerStateController,
     blank_mode: BlankMode,
 }
 
+/// Information from Lottie JSON, which rlottie doesn't expose
+#[derive(Debug, Clone, Default)]
+pub struct LottieInfo {
+    /// `nm`
+    pub name: Option<String>,
+    /// `v` (bodymovin version)
+    pub version: Option<String>,
+    /// `meta` (generator, author, keywords, ...)
+    pub meta: Vec<(String, String)>,
+    /// `ip` (first frame of the composition)
+    pub in_point: f64,
+    pub markers: Vec<LottieMarker>,
+}
+
+/// Named segment of a Lottie animation (`markers` in JSON)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LottieMarker {
+    /// `cm`
+    pub name: String,
+    /// `tm` (frame)
+    pub start_frame: f64,
+    /// `dr` (frames)
+    pub duration_frames: f64,
+}
+
+impl LottieMarker {
+    pub fn end_frame(&self) -> f64 {
+        self.start_frame + self.duration_frames
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkerNotFoundError {
+    pub name: String,
+}
+
+impl LottieInfo {
+    pub fn from_json(json_data: &[u8]) -> Self {
+        let Ok(json) = serde_json::from_slice::<serde_json::Value>(json_data) else {
+            warn!("Failed to parse Lottie JSON");
+            return Self::default();
+        };
+        let get_str = |key: &str| json.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let meta = json.get("meta")
+            .and_then(|v| v.as_object())
+            .map(|obj| obj.iter()
+                .filter_map(|(k, v)| v.as_str().filter(|v| !v.is_empty()).map(|v| (k.clone(), v.to_string())))
+                .collect())
+            .unwrap_or_default();
+
+        let markers = json.get("markers")
+            .and_then(|v| v.as_array())
+            .map(|markers| markers.iter()
+                .filter_map(|marker| Some(LottieMarker {
+                    name: marker.get("cm")?.as_str()?.to_string(),
+                    start_frame: marker.get("tm")?.as_f64()?,
+                    duration_frames: marker.get("dr").and_then(|v| v.as_f64()).unwrap_or(0.0),
+                }))
+                .collect())
+            .unwrap_or_default();
+
+        Self {
+            name: get_str("nm"),
+            version: get_str("v"),
+            meta,
+            in_point: json.get("ip").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            markers,
+        }
+    }
+}
+
+/// How frames between authored frames are rendered
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum LottieFrameInterpolation {
+    /// snap to authored frames (matches the authored frame rate)
+    #[default]
+    Stepped,
+    /// render sub-frames (up to 240fps), for smooth motion
+    Continuous,
+}
+
+/// Target frame rate of `LottieFrameInterpolation::Continuous`
+const CONTINUOUS_FRAME_RATE: f64 = 240.0;
+const MAX_SUBFRAMES: usize = 16;
+
+/// Multiply the timeline by `subframes`, so that rlottie can render sub-frames as integer frames.
+///
+/// Root layers are wrapped by a precomp layer with time stretch (`sr`), keyframes are untouched.
+fn subdivide_frames(json: &mut serde_json::Value, subframes: usize) {
+    use serde_json::json;
+
+    if subframes <= 1 {
+        return;
+    }
+    let n = subframes as f64;
+    let Some(obj) = json.as_object_mut() else {
+        return;
+    };
+    let get_f64 = |obj: &serde_json::Map<String, serde_json::Value>, key: &str| obj.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let (fr, ip, op) = (get_f64(obj, "fr"), get_f64(obj, "ip"), get_f64(obj, "op"));
+    let (w, h) = (get_f64(obj, "w"), get_f64(obj, "h"));
+    let layers = obj.remove("layers").unwrap_or(json!([]));
+
+    let id = "__subframes_root";
+    let assets = obj.entry("assets").or_insert(json!([]));
+    if let Some(assets) = assets.as_array_mut() {
+        assets.push(json!({ "id": id, "layers": layers }));
+    }
+    obj.insert("layers".to_string(), json!([{
+        "ty": 0, "nm": "", "ind": 1, "refId": id,
+        "w": w, "h": h,
+        "ip": ip * n, "op": op * n, "st": 0, "sr": n,
+        "ks": {
+            "o": { "a": 0, "k": 100 },
+            "r": { "a": 0, "k": 0 },
+            "p": { "a": 0, "k": [0, 0, 0] },
+            "a": { "a": 0, "k": [0, 0, 0] },
+            "s": { "a": 0, "k": [100, 100, 100] }
+        }
+    }]));
+    obj.insert("fr".to_string(), json!(fr * n));
+    obj.insert("ip".to_string(), json!(ip * n));
+    obj.insert("op".to_string(), json!(op * n));
+}
+
 #[derive(Asset, TypePath, Derivative)]
 #[derivative(Debug)]
 pub struct LottieMovie {
@@ -46,26 +223,51 @@ impl From<LottieMoviePlayer> for LottieMovie {
     }
 }
 
-#[derive(Default, TypePath)]
-pub struct LottieMovieLoader;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LottieMovieLoaderSettings {
+    /// render size (fit into, keeping aspect ratio). `None` = intrinsic size of the animation
+    pub render_size: Option<(u32, u32)>,
+    /// render playing frames in background (see `LottieMoviePlayer::set_threaded_rendering`)
+    pub threaded_rendering: bool,
+    pub frame_interpolation: LottieFrameInterpolation,
+}
+
+#[derive(TypePath)]
+pub struct LottieMovieLoader {
+    extensions: Vec<&'static str>,
+}
+
+impl Default for LottieMovieLoader {
+    fn default() -> Self {
+        Self::with_extensions(&default_lottie_extensions())
+    }
+}
+
+impl LottieMovieLoader {
+    pub fn with_extensions(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+        }
+    }
+}
 
 impl AssetLoader for LottieMovieLoader {
     type Asset = LottieMovie;
-    type Settings = ();
+    type Settings = LottieMovieLoaderSettings;
     type Error = std::io::Error;
   
     fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
-        _load_context: &mut LoadContext,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+      let settings = settings.clone();
       Box::pin(async move {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let cache_key = "".to_string();
-        let resource_path = "".to_string();
-        let player = load_lottie_from_data(bytes, cache_key, resource_path);
+        let mut player = load_lottie_from_asset_bytes(bytes, load_context).await?;
+        player.apply_loader_settings(&settings);
         // println!("Loaded Lottie {:?}", player);
         // println!("duration: {:?}", player.get_duration());
         Ok(player.into())
@@ -73,31 +275,165 @@ impl AssetLoader for LottieMovieLoader {
     }
   
     fn extensions(&self) -> &[&str] {
-      #[cfg(not(feature = "disable-json-extension-for-lottie"))]
-      {
-          &["json", "lottie.json"]
-      }
-
-      #[cfg(feature = "disable-json-extension-for-lottie")]
-      {
-            &["lottie.json"]
-      } 
+      &self.extensions
     }
   }
   
 
+/// Load Lottie JSON read by an asset loader (also used by `AnyMovieLoader`).
+///
+/// External images are read through the asset source and embedded.
+pub(crate) async fn load_lottie_from_asset_bytes(bytes: Vec<u8>, load_context: &mut LoadContext<'_>) -> std::io::Result<LottieMoviePlayer> {
+    let bytes = load_images_from_asset_source(bytes, load_context).await;
+
+    // NOTE: rlottie caches by key, so content hash is added for hot reloading
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let cache_key = format!("{}#{:x}", load_context.path(), hasher.finish());
+    // images are embedded, so resource path is not used
+    try_load_lottie_from_data(bytes, cache_key, "")
+}
+
+/// Image assets (`assets[]` with `p`) which are not embedded yet, as `(u, p)`
+pub fn get_external_image_paths(json: &serde_json::Value) -> Vec<(String, String)> {
+    json.get("assets")
+        .and_then(|v| v.as_array())
+        .map(|assets| assets.iter()
+            .filter(|asset| asset.get("e").and_then(|v| v.as_i64()) != Some(1))
+            .filter_map(|asset| {
+                let p = asset.get("p")?.as_str()?;
+                if p.starts_with("data:") {
+                    return None;
+                }
+                let u = asset.get("u").and_then(|v| v.as_str()).unwrap_or("");
+                Some((u.to_string(), p.to_string()))
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+fn get_image_mime_type(path: &str) -> &'static str {
+    let ext = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => "image/png",
+    }
+}
+
+/// Embed image assets as base64 data URI (`e: 1`), so rlottie doesn't need to read them from disk.
+///
+/// `resolve` gets `(u, p)` of each external image and returns its bytes.
+pub fn embed_images<F>(json: &mut serde_json::Value, mut resolve: F)
+    where F: FnMut(&str, &str) -> Option<Vec<u8>>
+{
+    use base64::Engine;
+
+    let Some(assets) = json.get_mut("assets").and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+    for asset in assets.iter_mut() {
+        if asset.get("e").and_then(|v| v.as_i64()) == Some(1) {
+            continue;
+        }
+        let Some(p) = asset.get("p").and_then(|v| v.as_str()).map(|s| s.to_string()) else {
+            continue;
+        };
+        if p.starts_with("data:") {
+            continue;
+        }
+        let u = asset.get("u").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let Some(bytes) = resolve(&u, &p) else {
+            warn!("Lottie image not found: {}{}", u, p);
+            continue;
+        };
+        let data_uri = format!("data:{};base64,{}",
+            get_image_mime_type(&p),
+            base64::engine::general_purpose::STANDARD.encode(bytes));
+        if let Some(obj) = asset.as_object_mut() {
+            obj.insert("u".to_string(), serde_json::Value::from(""));
+            obj.insert("p".to_string(), serde_json::Value::from(data_uri));
+            obj.insert("e".to_string(), serde_json::Value::from(1));
+        }
+    }
+}
+
+/// Read external images relative to the asset path (through the asset source, as load dependencies),
+/// and embed them into JSON
+async fn load_images_from_asset_source(json_data: Vec<u8>, load_context: &mut LoadContext<'_>) -> Vec<u8> {
+    let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(&json_data) else {
+        return json_data;
+    };
+    let image_paths = get_external_image_paths(&json);
+    if image_paths.is_empty() {
+        return json_data;
+    }
+
+    let mut images = HashMap::new();
+    for (u, p) in image_paths {
+        // NOTE: `u` is usually `images/` or `/images/`, both relative to the JSON
+        let relative_path = format!("{}{}", u, p);
+        let Ok(path) = load_context.path().resolve_embed(relative_path.trim_start_matches('/')) else {
+            warn!("Invalid Lottie image path: {}", relative_path);
+            continue;
+        };
+        match load_context.read_asset_bytes(path).await {
+            Ok(bytes) => { images.insert((u, p), bytes); },
+            Err(e) => warn!("Failed to read Lottie image {}: {}", relative_path, e),
+        }
+    }
+
+    embed_images(&mut json, |u, p| images.remove(&(u.to_string(), p.to_string())));
+    serde_json::to_vec(&json).unwrap_or(json_data)
+}
+
 /// Load a Lottie movie from a file
 pub fn load_lottie(path: &str) -> LottieMoviePlayer {
-    let lottie = LottieAnimation::from_file(path).unwrap();
+    try_load_lottie(path).expect("failed to load Lottie")
+}
+
+/// Load a Lottie movie from a file, without panic
+pub fn try_load_lottie(path: &str) -> std::io::Result<LottieMoviePlayer> {
+    let lottie = LottieAnimation::from_file(path)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid Lottie: {}", path)))?;
     let size = lottie.size();
     let lottie_surface = LottieSurface::new(size);
+    let frame_rate = lottie.framerate();
+    let total_frame = lottie.totalframe();
+    let duration = Duration::from_secs_f64(lottie.duration());
+    let intrinsic_size = get_resolution_of_lottie(&lottie);
+    let json_data = std::fs::read(path)?;
+    let info = LottieInfo::from_json(&json_data);
+    let resource_path = std::path::Path::new(path).parent().map(|p| p.to_path_buf()).unwrap_or_default();
     
-    LottieMoviePlayer {
+    Ok(LottieMoviePlayer {
         lottie: Arc::new(Mutex::new(lottie)),
         lottie_surface,
+        info,
+        json_data,
+        parsed_json: None,
+        resource_path,
+        overrides: vec![],
+        #[cfg(feature = "lottie-text")]
+        texts: vec![],
+        #[cfg(feature = "lottie-text")]
+        fonts: LottieFontMap::default(),
+        needs_rebuild: false,
+        unpremultiply_alpha: true,
+        frame_interpolation: LottieFrameInterpolation::default(),
+        subframes: 1,
+        frame_rate,
+        total_frame,
+        duration,
+        intrinsic_size,
+        render_worker: None,
+        front_frame: None,
+        generation: 0,
         state_controller: MoviePlayerStateController::default(),
         blank_mode: BlankMode::default(),
-    }
+    })
 }
 
 /// Load a Lottie movie from a data
@@ -107,20 +443,411 @@ pub fn load_lottie_from_data<D, K, P>(json_data: D, cache_key: K, resource_path:
 		K: Into<Vec<u8>>,
 		P: AsRef<std::path::Path>
 {
+    try_load_lottie_from_data(json_data, cache_key, resource_path).unwrap()
+}
+
+/// Load a Lottie movie from a data, or `InvalidData` error if rlottie can't parse it
+pub fn try_load_lottie_from_data<D, K, P>(json_data: D, cache_key: K, resource_path: P) -> std::io::Result<LottieMoviePlayer>
+	where
+		D: Into<Vec<u8>>,
+		K: Into<Vec<u8>>,
+		P: AsRef<std::path::Path>
+{
+    let json_data = json_data.into();
+    let resource_path = resource_path.as_ref().to_path_buf();
     let lottie = LottieAnimation::from_data(
-        json_data,
+        json_data.clone(),
         cache_key,
-        resource_path,
-    ).unwrap();
+        &resource_path,
+    ).ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid Lottie JSON"))?;
+    let info = LottieInfo::from_json(&json_data);
 
     let size = lottie.size();
     let lottie_surface = LottieSurface::new(size);
+    let frame_rate = lottie.framerate();
+    let total_frame = lottie.totalframe();
+    let duration = Duration::from_secs_f64(lottie.duration());
+    let intrinsic_size = get_resolution_of_lottie(&lottie);
     
-    LottieMoviePlayer {
+    Ok(LottieMoviePlayer {
         lottie: Arc::new(Mutex::new(lottie)),
         lottie_surface,
+        info,
+        json_data,
+        parsed_json: None,
+        resource_path,
+        overrides: vec![],
+        #[cfg(feature = "lottie-text")]
+        texts: vec![],
+        #[cfg(feature = "lottie-text")]
+        fonts: LottieFontMap::default(),
+        needs_rebuild: false,
+        unpremultiply_alpha: true,
+        frame_interpolation: LottieFrameInterpolation::default(),
+        subframes: 1,
+        frame_rate,
+        total_frame,
+        duration,
+        intrinsic_size,
+        render_worker: None,
+        front_frame: None,
+        generation: 0,
         state_controller: MoviePlayerStateController::default(),
         blank_mode: BlankMode::default(),
+    })
+}
+
+impl LottieMoviePlayer {
+    pub fn apply_loader_settings(&mut self, settings: &LottieMovieLoaderSettings) {
+        if let Some((width, height)) = settings.render_size {
+            self.set_render_size(width, height);
+        }
+        self.set_threaded_rendering(settings.threaded_rendering);
+        self.set_frame_interpolation(settings.frame_interpolation);
+    }
+
+    /// Render the frame at the position (premultiplied BGRA, same as `get_*_frame_bgra`), without changing the state.
+    ///
+    /// While the render worker is busy, the last frame it finished is returned instead.
+    pub fn render_frame_at(&mut self, t: Duration) -> Option<Vec<u8>> {
+        self.try_render(|lottie, surface| opt_bgra_to_u8(read_frame_at(lottie, surface, t)))
+    }
+
+    /// Render on the calling thread without waiting for the worker:
+    /// while it holds the animation, return the last finished frame (`None` if nothing is rendered yet)
+    fn try_render<F>(&mut self, render: F) -> Option<Vec<u8>>
+    where
+        F: FnOnce(&mut LottieAnimation, &mut LottieSurface) -> Option<Vec<u8>>,
+    {
+        self.rebuild_if_needed();
+        let rendered = match self.lottie.try_lock() {
+            Ok(mut lottie) => Some(render(&mut lottie, &mut self.lottie_surface)),
+            Err(_) => None,
+        };
+        match rendered {
+            Some(frame) => frame,
+            None => self.get_latest_rendered_frame(),
+        }
+    }
+
+    /// Last frame finished by the worker, of the current animation and size
+    fn get_latest_rendered_frame(&mut self) -> Option<Vec<u8>> {
+        let generation = self.generation;
+        if let Some(frame) = self.render_worker.as_ref()?.take_finished().filter(|frame| frame.generation == generation) {
+            self.front_frame = Some(frame);
+        }
+        let size = self.get_resolution();
+        self.front_frame.as_ref().filter(|frame| frame.size == size).map(|frame| frame.data.clone())
+    }
+
+    /// Size of the composition in Lottie JSON (`w`, `h`)
+    pub fn get_intrinsic_size(&self) -> (u32, u32) {
+        self.intrinsic_size
+    }
+
+    /// Rasterize at the size (fit into, keeping aspect ratio), instead of the intrinsic size.
+    ///
+    /// Reallocates the surface, so `get_resolution()` and the image size change.
+    pub fn set_render_size(&mut self, width: u32, height: u32) {
+        let size = fit_size(self.get_intrinsic_size(), (width, height));
+        if size == self.get_resolution() {
+            return;
+        }
+        self.lottie_surface = LottieSurface::new(rlottie::Size {
+            width: size.0 as usize,
+            height: size.1 as usize,
+        });
+    }
+
+    /// Convert premultiplied output of rlottie into straight alpha in `get_image_data` (default: true).
+    ///
+    /// Disable it when you draw the image with premultiplied blending (e.g. `AlphaMode::Premultiplied`).
+    pub fn set_unpremultiply_alpha(&mut self, enabled: bool) {
+        self.unpremultiply_alpha = enabled;
+    }
+
+    pub fn get_unpremultiply_alpha(&self) -> bool {
+        self.unpremultiply_alpha
+    }
+
+    /// Render playing frames on `AsyncComputeTaskPool`.
+    ///
+    /// While frame N is shown, frame N+1 is rendered in background (double buffered),
+    /// so many animations don't stall the schedule. The shown frame may be one frame behind.
+    pub fn set_threaded_rendering(&mut self, enabled: bool) {
+        if enabled == self.render_worker.is_some() {
+            return;
+        }
+        self.front_frame = None;
+        self.render_worker = enabled.then(|| LottieRenderWorker::new(self.lottie.clone()));
+    }
+
+    pub fn is_threaded_rendering(&self) -> bool {
+        self.render_worker.is_some()
+    }
+
+    /// Frame number at the position (floor, same as `frame_at_pos`)
+    fn get_frame_num_at(&self, t: Duration) -> usize {
+        let frame = (t.as_secs_f64() * self.frame_rate).floor() as usize;
+        frame.min(self.total_frame.saturating_sub(1))
+    }
+
+    /// Frame after `frame_num`, wrapping within the play range (`play_range` / `play_segment`) if set
+    fn get_next_frame_num(&self, frame_num: usize) -> usize {
+        let total_frame = self.total_frame.max(1);
+        let (start, end) = match self.get_play_range() {
+            Some((start, end)) => {
+                let start = self.get_frame_num_at(start);
+                let end = ((end.as_secs_f64() * self.frame_rate).ceil() as usize).clamp(start + 1, total_frame);
+                (start, end)
+            },
+            None => (0, total_frame),
+        };
+        let next = frame_num + 1;
+        if next < start || next >= end {
+            start
+        } else {
+            next
+        }
+    }
+
+    /// Take the frame from the worker and schedule the next one. Returns `None` if nothing is rendered yet.
+    fn get_threaded_frame_bgra(&mut self, frame_num: usize) -> Option<Vec<u8>> {
+        let size = self.get_resolution();
+        let generation = self.generation;
+        let next_frame_num = self.get_next_frame_num(frame_num);
+        let worker = self.render_worker.as_ref()?;
+
+        if let Some(frame) = worker.take_finished().filter(|frame| frame.generation == generation) {
+            self.front_frame = Some(frame);
+        }
+        let front = self.front_frame.as_ref().filter(|frame| frame.size == size)?;
+
+        if front.frame_num == frame_num {
+            // prefetch next frame
+            worker.request(RenderRequest { frame_num: next_frame_num, size, generation });
+        } else {
+            // late, or seeked: show the previous frame this time
+            worker.request(RenderRequest { frame_num, size, generation });
+        }
+        Some(front.data.clone())
+    }
+
+    /// Render at the intrinsic size again
+    pub fn reset_render_size(&mut self) {
+        let (width, height) = self.get_intrinsic_size();
+        self.set_render_size(width, height);
+    }
+
+    pub fn get_markers(&self) -> &[LottieMarker] {
+        &self.info.markers
+    }
+
+    pub fn get_marker(&self, name: &str) -> Option<&LottieMarker> {
+        self.info.markers.iter().find(|marker| marker.name == name)
+    }
+
+    fn frame_to_duration(&self, frame: f64) -> Duration {
+        let framerate = self.get_authored_frame_rate();
+        Duration::from_secs_f64(((frame - self.info.in_point) / framerate).max(0.0))
+    }
+
+    /// Play frames `[start_frame, end_frame)`, used as loop range by `LoopMode`
+    pub fn play_frames(&mut self, start_frame: f64, end_frame: f64) {
+        let start = self.frame_to_duration(start_frame);
+        let end = self.frame_to_duration(end_frame);
+        self.play_range(start, end);
+    }
+
+    /// Play a marker (named segment), used as loop range by `LoopMode`
+    pub fn play_segment(&mut self, name: &str) -> Result<(), MarkerNotFoundError> {
+        let Some(marker) = self.get_marker(name) else {
+            return Err(MarkerNotFoundError {
+                name: name.to_string(),
+            });
+        };
+        let (start_frame, end_frame) = (marker.start_frame, marker.end_frame());
+        self.play_frames(start_frame, end_frame);
+        Ok(())
+    }
+
+    /// Override a property of layers / shapes matched by keypath (e.g. `**.Fill 1`, `Layer.Group`).
+    ///
+    /// Overrides are applied in order, and take effect on the next rendered frame.
+    /// Changes are batched: the animation is rebuilt once on the next rendered frame, however many are set.
+    /// An override of the same keypath and property kind is replaced (moved to the last),
+    /// and setting the same value again does nothing (e.g. when set every frame).
+    pub fn set_property(&mut self, keypath: &str, property: LottieProperty) {
+        if self.overrides.iter().any(|(k, p)| k == keypath && *p == property) {
+            return;
+        }
+        self.overrides.retain(|(k, p)| k != keypath || mem::discriminant(p) != mem::discriminant(&property));
+        self.overrides.push((keypath.to_string(), property));
+        self.needs_rebuild = true;
+    }
+
+    pub fn set_fill_color(&mut self, keypath: &str, color: Color) {
+        self.set_property(keypath, LottieProperty::FillColor(color));
+    }
+
+    pub fn set_stroke_color(&mut self, keypath: &str, color: Color) {
+        self.set_property(keypath, LottieProperty::StrokeColor(color));
+    }
+
+    pub fn set_stroke_width(&mut self, keypath: &str, width: f32) {
+        self.set_property(keypath, LottieProperty::StrokeWidth(width));
+    }
+
+    /// 0.0 - 100.0
+    pub fn set_fill_opacity(&mut self, keypath: &str, opacity: f32) {
+        self.set_property(keypath, LottieProperty::FillOpacity(opacity));
+    }
+
+    /// 0.0 - 100.0
+    pub fn set_stroke_opacity(&mut self, keypath: &str, opacity: f32) {
+        self.set_property(keypath, LottieProperty::StrokeOpacity(opacity));
+    }
+
+    /// Opacity of layers / groups (transform `o`, 0.0 - 100.0), which multiplies everything in them.
+    ///
+    /// Fill and stroke opacity are kept, use `set_fill_opacity` / `set_stroke_opacity` for shapes.
+    pub fn set_opacity(&mut self, keypath: &str, opacity: f32) {
+        self.set_property(keypath, LottieProperty::TransformOpacity(opacity));
+    }
+
+    pub fn set_transform_position(&mut self, keypath: &str, position: Vec2) {
+        self.set_property(keypath, LottieProperty::TransformPosition(position));
+    }
+
+    /// percent (100.0 = original size)
+    pub fn set_transform_scale(&mut self, keypath: &str, scale: Vec2) {
+        self.set_property(keypath, LottieProperty::TransformScale(scale));
+    }
+
+    /// degrees
+    pub fn set_transform_rotation(&mut self, keypath: &str, degrees: f32) {
+        self.set_property(keypath, LottieProperty::TransformRotation(degrees));
+    }
+
+    pub fn get_properties(&self) -> &[(String, LottieProperty)] {
+        &self.overrides
+    }
+
+    /// Remove all overrides and restore the original animation
+    pub fn clear_properties(&mut self) {
+        if !self.overrides.is_empty() {
+            self.overrides.clear();
+            self.needs_rebuild = true;
+        }
+    }
+
+    /// Text layers (`ty: 5`) in root and precomps, with the original text
+    #[cfg(feature = "lottie-text")]
+    pub fn get_text_layers(&self) -> Vec<LottieTextLayer> {
+        serde_json::from_slice(&self.json_data)
+            .map(|json| crate::lottie_text::get_text_layers(&json))
+            .unwrap_or_default()
+    }
+
+    /// Replace text of text layers named `layer_name`.
+    ///
+    /// NOTE: rlottie doesn't render text layers, so they are rendered as shapes
+    /// with the mapped font (see `set_font`), and stay invisible without it.
+    #[cfg(feature = "lottie-text")]
+    pub fn set_text(&mut self, layer_name: &str, text: &str) {
+        self.texts.retain(|(name, _)| name != layer_name);
+        self.texts.push((layer_name.to_string(), text.to_string()));
+        self.needs_rebuild = true;
+    }
+
+    /// Map Lottie font (`fName` or `fFamily`) to Bevy `Font` asset
+    #[cfg(feature = "lottie-text")]
+    pub fn set_font(&mut self, font_name: &str, font: &bevy::text::Font) {
+        self.fonts.insert(font_name, font.data.clone(), 0);
+        self.needs_rebuild = true;
+    }
+
+    /// Map Lottie font (`fName` or `fFamily`) to system font by family name.
+    /// Returns `false` if not found.
+    #[cfg(feature = "lottie-text")]
+    pub fn set_system_font(&mut self, font_name: &str, family: &str) -> bool {
+        let found = self.fonts.insert_system_font(font_name, family);
+        if found {
+            self.needs_rebuild = true;
+        }
+        found
+    }
+
+    /// Stepped (default): render frames at the authored frame rate.
+    /// Continuous: render sub-frames for smooth motion on high refresh rate displays.
+    pub fn set_frame_interpolation(&mut self, interpolation: LottieFrameInterpolation) {
+        if interpolation != self.frame_interpolation {
+            self.frame_interpolation = interpolation;
+            self.needs_rebuild = true;
+        }
+    }
+
+    pub fn get_frame_interpolation(&self) -> LottieFrameInterpolation {
+        self.frame_interpolation
+    }
+
+    /// Authored frame rate (`fr`)
+    fn get_authored_frame_rate(&self) -> f64 {
+        self.frame_rate / self.subframes as f64
+    }
+
+    /// Rebuild the animation from JSON with overrides, text and sub-frames
+    /// (rlottie-rs doesn't expose property override, and rlottie renders integer frames only)
+    fn rebuild_if_needed(&mut self) {
+        if !self.needs_rebuild {
+            return;
+        }
+        self.needs_rebuild = false;
+
+        let subframes = match self.frame_interpolation {
+            LottieFrameInterpolation::Stepped => 1,
+            LottieFrameInterpolation::Continuous => {
+                (CONTINUOUS_FRAME_RATE / self.get_authored_frame_rate()).ceil().clamp(1.0, MAX_SUBFRAMES as f64) as usize
+            },
+        };
+
+        #[cfg(feature = "lottie-text")]
+        let has_text_changes = !self.texts.is_empty() || !self.fonts.is_empty();
+        #[cfg(not(feature = "lottie-text"))]
+        let has_text_changes = false;
+
+        let json_data = if self.overrides.is_empty() && subframes == 1 && !has_text_changes {
+            self.json_data.clone()
+        } else {
+            if self.parsed_json.is_none() {
+                self.parsed_json = serde_json::from_slice(&self.json_data).ok();
+            }
+            let Some(mut json) = self.parsed_json.clone() else {
+                warn!("Failed to parse Lottie JSON, property overrides and sub-frames are ignored");
+                return;
+            };
+            #[cfg(feature = "lottie-text")]
+            convert_text_layers(&mut json, &self.texts, &self.fonts);
+            apply_properties(&mut json, &self.overrides);
+            subdivide_frames(&mut json, subframes);
+            serde_json::to_vec(&json).unwrap_or_default()
+        };
+
+        // NOTE: empty cache key, not to get the cached (original) animation
+        match LottieAnimation::from_data(json_data, "", &self.resource_path) {
+            Some(lottie) => {
+                self.frame_rate = lottie.framerate();
+                self.total_frame = lottie.totalframe();
+                self.duration = Duration::from_secs_f64(lottie.duration());
+                self.intrinsic_size = get_resolution_of_lottie(&lottie);
+                self.subframes = subframes;
+                *self.lottie.lock().unwrap() = lottie;
+                // frames rendered (or being rendered) by the worker are of the old animation
+                self.generation += 1;
+                self.front_frame = None;
+            },
+            None => warn!("Failed to rebuild Lottie animation"),
+        }
     }
 }
 
@@ -134,8 +861,7 @@ impl MoviePlayer for LottieMoviePlayer {
     }
     
     fn get_duration(&self) -> Duration {
-        let lottie = self.lottie.lock().unwrap();
-        Duration::from_secs_f64(lottie.duration())
+        self.duration
     }
 
     fn set_volume(&mut self, _volume: f32) {
@@ -148,10 +874,29 @@ impl MoviePlayer for LottieMoviePlayer {
         0.0
     }
     
+    /// render size (see `set_render_size`)
     fn get_resolution(&self) -> (u32, u32) {
-        let lottie = self.lottie.lock().unwrap();
-        let size = lottie.size();
-        (size.width as u32, size.height as u32)
+        (self.lottie_surface.width() as u32, self.lottie_surface.height() as u32)
+    }
+
+    fn get_metadata(&self) -> MovieMetadata {
+        let mut tags = self.info.meta.clone();
+        if let Some(version) = &self.info.version {
+            tags.push(("version".to_string(), version.clone()));
+        }
+
+        MovieMetadata {
+            codec: Some("lottie".to_string()),
+            container: Some("json".to_string()),
+            frame_rate: Some(self.get_authored_frame_rate() as f32),
+            bit_depth: Some(8),
+            color_primaries: Some("bt709".to_string()),
+            color_transfer: Some("iec61966-2-1".to_string()),
+            pixel_aspect_ratio: Some((1, 1)),
+            title: self.info.name.clone(),
+            tags,
+            ..Default::default()
+        }
     }
 }
 
@@ -194,6 +939,103 @@ fn get_resolution_of_lottie(lottie: &rlottie::Animation) -> (u32, u32) {
     (size.width as u32, size.height as u32)
 }
 
+/// Check references of the animation and render all frames. Returns found problems:
+///
+/// - precomp / image layers (`refId`) whose asset doesn't exist
+/// - external images which don't exist in the resource path
+/// - every frame is fully transparent (e.g. all layers hidden, or out of the frame range)
+///
+/// NOTE: rlottie renders unsupported features as nothing, so they are not detected.
+pub fn validate_lottie(player: &mut LottieMoviePlayer) -> Vec<String> {
+    let json = match serde_json::from_slice::<serde_json::Value>(&player.json_data) {
+        Ok(json) => json,
+        Err(e) => return vec![format!("invalid JSON: {}", e)],
+    };
+    let mut errors = vec![];
+
+    let assets = json.get("assets").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let asset_ids: Vec<&str> = assets.iter().filter_map(|asset| asset.get("id")?.as_str()).collect();
+    let layer_lists = std::iter::once(json.get("layers"))
+        .chain(assets.iter().map(|asset| asset.get("layers")))
+        .filter_map(|layers| layers?.as_array());
+    for layer in layer_lists.flatten() {
+        if let Some(ref_id) = layer.get("refId").and_then(|v| v.as_str()) {
+            if !asset_ids.contains(&ref_id) {
+                let name = layer.get("nm").and_then(|v| v.as_str()).unwrap_or("");
+                errors.push(format!("layer {:?}: asset {:?} not found", name, ref_id));
+            }
+        }
+    }
+
+    for (u, p) in get_external_image_paths(&json) {
+        let path = player.resource_path.join(u.trim_start_matches('/')).join(&p);
+        if !path.is_file() {
+            errors.push(format!("image not found: {}", path.display()));
+        }
+    }
+
+    player.rebuild_if_needed();
+    let total_frame = player.total_frame;
+    let mut lottie = player.lottie.lock().unwrap();
+    let is_empty = (0..total_frame).all(|frame_num| {
+        read_frame(&mut lottie, &mut player.lottie_surface, frame_num)
+            .is_none_or(|frame| get_bgra_data_as_bytes(frame).chunks_exact(4).all(|px| px[3] == 0))
+    });
+    if is_empty {
+        errors.push("all frames are fully transparent".to_string());
+    }
+    errors
+}
+
+/// Fit `size` into `bounds` keeping aspect ratio (at least 1x1)
+fn fit_size(size: (u32, u32), bounds: (u32, u32)) -> (u32, u32) {
+    if size.0 == 0 || size.1 == 0 {
+        return (bounds.0.max(1), bounds.1.max(1));
+    }
+    let scale = (bounds.0 as f64 / size.0 as f64).min(bounds.1 as f64 / size.1 as f64);
+    (
+        ((size.0 as f64 * scale).round() as u32).max(1),
+        ((size.1 as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Resize rendering of the Lottie movie to the computed size of the UI node (in physical pixels).
+///
+/// Applied when the node is resized, the component is added or changed, or the movie is loaded.
+/// Update the image with `set_image_data` as usual, then the image is reallocated with the new size.
+#[cfg(feature = "ui")]
+#[derive(Component, Debug, Clone)]
+pub struct LottieAutoRenderSize {
+    pub movie: Handle<LottieMovie>,
+}
+
+#[cfg(feature = "ui")]
+pub fn update_lottie_auto_render_sizes(
+    query: Query<(Ref<LottieAutoRenderSize>, Ref<ComputedNode>)>,
+    mut asset_events: MessageReader<AssetEvent<LottieMovie>>,
+    mut movies: ResMut<Assets<LottieMovie>>,
+) {
+    let loaded: Vec<AssetId<LottieMovie>> = asset_events.read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (auto_size, node) in &query {
+        let is_changed = auto_size.is_changed() || node.is_changed() || loaded.contains(&auto_size.movie.id());
+        if !is_changed {
+            continue;
+        }
+        let size = node.size();
+        if size.x < 1.0 || size.y < 1.0 {
+            continue;
+        }
+        if let Some(movie) = movies.get_mut(&auto_size.movie) {
+            movie.player.set_render_size(size.x.round() as u32, size.y.round() as u32);
+        }
+    }
+}
+
 
 fn opt_bgra_to_u8(frame_or_not: Option<&[Bgra]>) -> Option<Vec<u8>> {
     if let Some(frame) = frame_or_not {
@@ -204,34 +1046,173 @@ fn opt_bgra_to_u8(frame_or_not: Option<&[Bgra]>) -> Option<Vec<u8>> {
     }
 }
 
+struct RenderRequest {
+    frame_num: usize,
+    size: (u32, u32),
+    generation: u64,
+}
+
+struct RenderedFrame {
+    frame_num: usize,
+    size: (u32, u32),
+    generation: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct RenderWorkerState {
+    /// latest request (older one is replaced)
+    request: Option<RenderRequest>,
+    /// `(frame_num, generation)`
+    rendering: Option<(usize, u64)>,
+    finished: Option<RenderedFrame>,
+    /// back buffer, owned by the render task while rendering
+    surface: Option<LottieSurface>,
+    task_running: bool,
+    stop: bool,
+}
+
+/// Renders frames into its own (back) surface on `AsyncComputeTaskPool`
+struct LottieRenderWorker {
+    lottie: Arc<Mutex<LottieAnimation>>,
+    state: Arc<Mutex<RenderWorkerState>>,
+}
+
+impl LottieRenderWorker {
+    fn new(lottie: Arc<Mutex<LottieAnimation>>) -> Self {
+        Self {
+            lottie,
+            state: Arc::new(Mutex::new(RenderWorkerState::default())),
+        }
+    }
+
+    fn request(&self, request: RenderRequest) {
+        let mut state = self.state.lock().unwrap();
+        let already = state.rendering == Some((request.frame_num, request.generation))
+            || state.finished.as_ref().is_some_and(|f| {
+                f.frame_num == request.frame_num && f.size == request.size && f.generation == request.generation
+            });
+        if already {
+            return;
+        }
+        state.request = Some(request);
+        // one task at a time, which takes requests until none is left
+        if !state.task_running {
+            state.task_running = true;
+            AsyncComputeTaskPool::get_or_init(TaskPool::default)
+                .spawn(render_requests(self.lottie.clone(), self.state.clone()))
+                .detach();
+        }
+    }
+
+    fn take_finished(&self) -> Option<RenderedFrame> {
+        self.state.lock().unwrap().finished.take()
+    }
+}
+
+impl Drop for LottieRenderWorker {
+    fn drop(&mut self) {
+        // running task exits after the current frame
+        self.state.lock().unwrap().stop = true;
+    }
+}
+
+async fn render_requests(lottie: Arc<Mutex<LottieAnimation>>, state: Arc<Mutex<RenderWorkerState>>) {
+    loop {
+        let (request, surface) = {
+            let mut state = state.lock().unwrap();
+            let request = match state.request.take() {
+                Some(request) if !state.stop => request,
+                _ => {
+                    state.task_running = false;
+                    return;
+                },
+            };
+            state.rendering = Some((request.frame_num, request.generation));
+            (request, state.surface.take())
+        };
+
+        let size = (request.size.0 as usize, request.size.1 as usize);
+        let mut surface = surface
+            .filter(|s| (s.width(), s.height()) == size)
+            .unwrap_or_else(|| LottieSurface::new(rlottie::Size {
+                width: size.0,
+                height: size.1,
+            }));
+        lottie.lock().unwrap().render(request.frame_num, &mut surface);
+        let data = get_bgra_from_data(surface.data());
+
+        let mut state = state.lock().unwrap();
+        state.rendering = None;
+        state.surface = Some(surface);
+        state.finished = Some(RenderedFrame {
+            frame_num: request.frame_num,
+            size: request.size,
+            generation: request.generation,
+            data,
+        });
+    }
+}
+
 impl BGRAImageFrameProvider for LottieMoviePlayer {
     fn get_first_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let mut lottie= self.lottie.lock().unwrap();
-        let frame_or_not = read_frame(&mut lottie, &mut self.lottie_surface, 0);
-        opt_bgra_to_u8(frame_or_not)
+        self.try_render(|lottie, surface| opt_bgra_to_u8(read_frame(lottie, surface, 0)))
     }
 
     fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let mut lottie= self.lottie.lock().unwrap();
-        let total_frame = lottie.totalframe();
-        let frame_or_not = read_frame(&mut lottie, &mut self.lottie_surface, total_frame - 1);
-        opt_bgra_to_u8(frame_or_not)
+        self.rebuild_if_needed();
+        let last_frame = self.total_frame.saturating_sub(1);
+        self.try_render(|lottie, surface| opt_bgra_to_u8(read_frame(lottie, surface, last_frame)))
     }
 
     fn get_paused_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let mut lottie= self.lottie.lock().unwrap();
         let position = self.get_position();
-        let frame_or_not =
-            read_frame_at(&mut lottie, &mut self.lottie_surface, position);
-        opt_bgra_to_u8(frame_or_not)
+        self.try_render(|lottie, surface| opt_bgra_to_u8(read_frame_at(lottie, surface, position)))
+    }
+
+    /// exact frame for `FrameGrabber` (e.g. baking), so this waits for the worker instead of the last finished frame
+    fn get_grabbed_frame_bgra(&mut self) -> Option<Vec<u8>> {
+        self.rebuild_if_needed();
+        let position = self.get_position();
+        let mut lottie = self.lottie.lock().ok()?;
+        opt_bgra_to_u8(read_frame_at(&mut lottie, &mut self.lottie_surface, position))
     }
 
     fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>> {
-        let mut lottie= self.lottie.lock().unwrap();
+        self.rebuild_if_needed();
+        if self.render_worker.is_some() {
+            let frame_num = self.get_frame_num_at(self.get_position());
+            if let Some(frame) = self.get_threaded_frame_bgra(frame_num) {
+                return Some(frame);
+            }
+            // nothing rendered yet: render synchronously once if the worker is idle (never wait for it),
+            // and let the worker continue
+            let frame = match self.lottie.try_lock() {
+                Ok(mut lottie) => opt_bgra_to_u8(read_frame(&mut lottie, &mut self.lottie_surface, frame_num)),
+                Err(_) => None,
+            };
+            if let Some(data) = &frame {
+                self.front_frame = Some(RenderedFrame {
+                    frame_num,
+                    size: self.get_resolution(),
+                    generation: self.generation,
+                    data: data.clone(),
+                });
+                self.get_threaded_frame_bgra(frame_num);
+            }
+            return frame;
+        }
         let position = self.get_position();
-        let frame_or_not =
-            read_frame_at(&mut lottie, &mut self.lottie_surface, position);
-        opt_bgra_to_u8(frame_or_not)
+        self.try_render(|lottie, surface| opt_bgra_to_u8(read_frame_at(lottie, surface, position)))
+    }
+
+    /// rlottie renders premultiplied BGRA
+    fn is_premultiplied_alpha(&self) -> bool {
+        true
+    }
+
+    fn should_unpremultiply_alpha(&self) -> bool {
+        self.unpremultiply_alpha
     }
 }
 
@@ -239,6 +1220,7 @@ impl BGRAImageFrameProvider for LottieMoviePlayer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::movie_player::LoopMode;
 
     #[test]
     fn it_works() {
@@ -252,6 +1234,244 @@ mod tests {
         movie.stop();
     }
 
+    #[test]
+    fn segment() {
+        let json = r#"{"v":"5.7.0","fr":30,"ip":0,"op":90,"w":64,"h":64,"layers":[],
+            "markers":[{"cm":"hover_in","tm":30,"dr":15},{"cm":"idle","tm":60,"dr":30}]}"#;
+        let mut movie = load_lottie_from_data(json, "segment", "");
+        assert_eq!(movie.get_markers().len(), 2);
+        assert_eq!(movie.get_marker("idle").unwrap().end_frame(), 90.0);
+
+        movie.set_loop_mode(LoopMode::Loop);
+        movie.update(Duration::from_secs(10));
+        movie.play_segment("hover_in").unwrap();
+        assert_eq!(movie.get_position(), Duration::from_secs(1));
+        assert_eq!(movie.get_play_range(), Some((Duration::from_secs(1), Duration::from_secs_f64(1.5))));
+
+        // loop back to segment start
+        movie.update(Duration::from_secs_f64(10.6));
+        assert_eq!(movie.get_position(), Duration::from_secs(1));
+
+        assert!(movie.play_segment("press").is_err());
+    }
+
+    #[test]
+    fn validate() {
+        let mut movie = load_lottie("test_assets/test.json");
+        movie.set_render_size(32, 32);
+        assert_eq!(validate_lottie(&mut movie), Vec::<String>::new());
+
+        let json = r#"{"v":"5.7.0","fr":10,"ip":0,"op":10,"w":4,"h":4,
+            "assets":[{"id":"image_0","w":1,"h":1,"u":"images/","p":"missing.png","e":0}],
+            "layers":[{"ty":0,"nm":"Comp","refId":"comp_0","ip":0,"op":10,"st":0,"ks":{},"w":4,"h":4}]}"#;
+        let mut movie = load_lottie_from_data(json, "validate", "test_assets");
+        let errors = validate_lottie(&mut movie);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].contains("comp_0"));
+        assert!(errors[1].contains("missing.png"));
+        assert_eq!(errors[2], "all frames are fully transparent");
+    }
+
+    #[test]
+    fn metadata() {
+        let movie = load_lottie("test_assets/test.json");
+        let metadata = movie.get_metadata();
+        assert_eq!(metadata.codec, Some("lottie".to_string()));
+        assert_eq!(metadata.frame_rate, Some(60.0));
+        assert_eq!(metadata.title, Some("LEGO".to_string()));
+    }
+
+    #[test]
+    fn property_override() {
+        let json = r#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":4,"h":4,"layers":[
+            {"ty":4,"nm":"Box","ip":0,"op":30,"st":0,"ks":{},"shapes":[
+                {"ty":"rc","nm":"Rect","p":{"a":0,"k":[2,2]},"s":{"a":0,"k":[4,4]},"r":{"a":0,"k":0}},
+                {"ty":"fl","nm":"Fill 1","c":{"a":0,"k":[0,0,0,1]},"o":{"a":0,"k":100}}
+            ]}]}"#;
+        let mut movie = load_lottie_from_data(json, "property_override", "");
+        movie.set_fill_color("Box.Fill 1", Color::srgb(0.0, 1.0, 0.0));
+        movie.set_fill_color("Box.Fill 1", Color::srgb(1.0, 0.0, 0.0));
+        assert_eq!(movie.get_properties().len(), 1);
+
+        // batched: rebuilt once on the next frame, and the same value again is no-op
+        assert!(movie.needs_rebuild);
+        movie.get_first_frame_bgra();
+        movie.set_fill_color("Box.Fill 1", Color::srgb(1.0, 0.0, 0.0));
+        assert!(!movie.needs_rebuild);
+
+        // BGRA
+        let frame = movie.get_first_frame_bgra().unwrap();
+        assert_eq!(&frame[0..4], &[0, 0, 255, 255]);
+
+        // other kind (or keypath) is kept
+        movie.set_stroke_width("Box.Fill 1", 2.0);
+        movie.set_fill_color("Box", Color::srgb(1.0, 0.0, 0.0));
+        assert_eq!(movie.get_properties().len(), 3);
+
+        movie.clear_properties();
+        let frame = movie.get_first_frame_bgra().unwrap();
+        assert_eq!(&frame[0..4], &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn render_size() {
+        let mut movie = load_lottie("test_assets/test.json");
+        movie.set_render_size(64, 128);
+        assert_eq!(movie.get_resolution(), (64, 64));
+        assert_eq!(movie.get_intrinsic_size(), (1024, 1024));
+        assert_eq!(movie.get_first_frame_bgra().unwrap().len(), 64 * 64 * 4);
+
+        assert_eq!(fit_size((200, 100), (50, 50)), (50, 25));
+        movie.reset_render_size();
+        assert_eq!(movie.get_resolution(), (1024, 1024));
+    }
+
+    #[test]
+    fn external_images() {
+        let mut json: serde_json::Value = serde_json::from_str(r#"{"assets":[
+            {"id":"image_0","u":"images/","p":"img_0.png","e":0},
+            {"id":"image_1","u":"","p":"data:image/png;base64,AA==","e":1},
+            {"id":"comp_0","layers":[]}
+        ]}"#).unwrap();
+        assert_eq!(get_external_image_paths(&json), vec![("images/".to_string(), "img_0.png".to_string())]);
+
+        embed_images(&mut json, |u, p| (u == "images/" && p == "img_0.png").then(|| vec![0xFF]));
+        assert_eq!(json["assets"][0]["p"], "data:image/png;base64,/w==");
+        assert!(get_external_image_paths(&json).is_empty());
+    }
+
+    #[test]
+    fn threaded_rendering() {
+        let mut movie = load_lottie("test_assets/test.json");
+        movie.set_render_size(32, 32);
+        movie.set_threaded_rendering(true);
+        movie.play();
+
+        // first frame is rendered synchronously, next one is prefetched
+        assert_eq!(movie.get_playing_frame_bgra().unwrap().len(), 32 * 32 * 4);
+        let is_prefetched = |movie: &LottieMoviePlayer| movie.render_worker.as_ref().unwrap()
+            .state.lock().unwrap().finished.as_ref().is_some_and(|frame| frame.frame_num == 1);
+        let started = std::time::Instant::now();
+        while !is_prefetched(&movie) && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(is_prefetched(&movie));
+
+        // 60fps: frame 1
+        movie.update(Duration::from_millis(25));
+        assert_eq!(movie.get_playing_frame_bgra().unwrap().len(), 32 * 32 * 4);
+        assert_eq!(movie.front_frame.as_ref().map(|frame| frame.frame_num), Some(1));
+        assert!(movie.get_duration() > Duration::ZERO);
+
+        // frames of the animation before rebuild are not shown
+        movie.set_opacity("**", 50.0);
+        let started = std::time::Instant::now();
+        while movie.get_playing_frame_bgra().is_none() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(movie.generation, 1);
+        assert_eq!(movie.front_frame.as_ref().map(|frame| frame.generation), Some(1));
+
+        movie.set_threaded_rendering(false);
+        assert!(!movie.is_threaded_rendering());
+    }
+
+    #[test]
+    fn next_frame_in_play_range() {
+        let mut movie = load_lottie("test_assets/test.json");
+        let total_frame = movie.total_frame;
+        assert_eq!(movie.get_next_frame_num(0), 1);
+        assert_eq!(movie.get_next_frame_num(total_frame - 1), 0);
+
+        let frame_duration = |frame: f64| Duration::from_secs_f64(frame / movie.frame_rate);
+        let (start, end) = (frame_duration(10.0), frame_duration(20.0));
+        movie.play_range(start, end);
+        assert_eq!(movie.get_next_frame_num(10), 11);
+        assert_eq!(movie.get_next_frame_num(19), 10);
+        assert_eq!(movie.get_next_frame_num(total_frame - 1), 10);
+    }
+
+    #[test]
+    fn straight_alpha_edge() {
+        use crate::image_data_provider::ImageDataProvider;
+
+        // white rect covering half of the edge columns (x: 0.5 - 3.5)
+        let json = r#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":4,"h":4,"layers":[
+            {"ty":4,"nm":"Box","ip":0,"op":30,"st":0,"ks":{},"shapes":[
+                {"ty":"rc","nm":"Rect","p":{"a":0,"k":[2,2]},"s":{"a":0,"k":[3,4]},"r":{"a":0,"k":0}},
+                {"ty":"fl","nm":"Fill 1","c":{"a":0,"k":[1,1,1,1]},"o":{"a":0,"k":100}}
+            ]}]}"#;
+        let mut movie = load_lottie_from_data(json, "straight_alpha_edge", "");
+
+        let premultiplied = movie.get_first_frame_bgra().unwrap();
+        let straight = movie.get_image_data().data;
+        let edge = &premultiplied[0..4];
+        assert!(edge[3] > 0 && edge[3] < 255);
+        // premultiplied edge is darker than the fill
+        assert!(edge[0] < 200);
+        // straight edge keeps the fill color
+        assert!(straight[0..3].iter().all(|&c| c >= 250));
+        assert_eq!(straight[3], edge[3]);
+
+        movie.set_unpremultiply_alpha(false);
+        assert_eq!(movie.get_image_data().data, premultiplied);
+    }
+
+    #[test]
+    fn frame_interpolation() {
+        let json = r#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":4,"h":4,"layers":[
+            {"ty":4,"nm":"Box","ip":0,"op":30,"st":0,"ks":{},"shapes":[
+                {"ty":"rc","nm":"Rect","p":{"a":0,"k":[2,2]},"s":{"a":0,"k":[4,4]},"r":{"a":0,"k":0}},
+                {"ty":"fl","nm":"Fill 1","o":{"a":0,"k":100},"c":{"a":1,"k":[
+                    {"t":0,"s":[0,0,0,1],"e":[1,0,0,1],"i":{"x":[1],"y":[1]},"o":{"x":[0],"y":[0]}},
+                    {"t":1,"s":[1,0,0,1]}
+                ]}}
+            ]}]}"#;
+        let mut movie = load_lottie_from_data(json, "frame_interpolation", "");
+        let stepped = movie.get_first_frame_bgra().unwrap();
+        let stepped_next = movie.render_frame_at(Duration::from_secs_f64(1.2 / 30.0)).unwrap();
+        // black to red in one authored frame
+        assert_eq!(&stepped[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&stepped_next[0..4], &[0, 0, 255, 255]);
+        assert_eq!(movie.render_frame_at(Duration::from_secs_f64(0.5 / 30.0)).unwrap(), stepped);
+
+        movie.set_frame_interpolation(LottieFrameInterpolation::Continuous);
+        let continuous = movie.get_first_frame_bgra().unwrap();
+        assert_eq!(stepped, continuous);
+        // 30fps * 8 sub-frames
+        assert_eq!(movie.total_frame, 240);
+        assert_eq!(movie.get_duration(), Duration::from_secs(1));
+        assert_eq!(movie.get_metadata().frame_rate, Some(30.0));
+        assert_eq!(movie.get_frame_num_at(Duration::from_secs_f64(1.5 / 30.0)), 12);
+
+        // in-between sub-frame differs from both authored frames
+        let subframe = movie.render_frame_at(Duration::from_secs_f64(0.5 / 30.0)).unwrap();
+        assert_ne!(&subframe[0..4], &stepped[0..4]);
+        assert_ne!(&subframe[0..4], &stepped_next[0..4]);
+        assert_eq!(movie.render_frame_at(Duration::from_secs_f64(1.2 / 30.0)).unwrap(), stepped_next);
+    }
+
+    #[cfg(feature = "lottie-text")]
+    #[test]
+    fn text_layer() {
+        let json = r#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":64,"h":32,
+            "fonts":{"list":[{"fName":"DejaVuSans","fFamily":"DejaVu Sans"}]},
+            "layers":[{"ty":5,"nm":"Title","ip":0,"op":30,"st":0,"ks":{"p":{"a":0,"k":[0,24]}},
+                "t":{"d":{"k":[{"s":{"s":24,"f":"DejaVuSans","t":"","fc":[1,1,1]},"t":0}]}}}]}"#;
+        let mut movie = load_lottie_from_data(json, "text_layer", "");
+        assert_eq!(movie.get_text_layers()[0].name, "Title");
+        if !movie.set_system_font("DejaVuSans", "DejaVu Sans") {
+            eprintln!("DejaVu Sans not found, skipped");
+            return;
+        }
+        let empty = movie.get_first_frame_bgra().unwrap();
+        assert!(empty.chunks(4).all(|px| px[3] == 0));
+
+        movie.set_text("Title", "AB");
+        let frame = movie.get_first_frame_bgra().unwrap();
+        assert!(frame.chunks(4).any(|px| px[3] > 0));
+    }
+
     // TODO: add loop test
     // TODO: add seek test
     // TODO: add image data test
@@ -0,0 +1,131 @@
+use gv_video::GVFormat;
+
+use crate::gv_encode::encode_movie_to_gv;
+use crate::gv_encode::GVEncodeSettings;
+use crate::lottie::LottieMoviePlayer;
+use crate::movie_player::MoviePlayer;
+
+use std::io::Seek;
+use std::io::Write;
+
+/// Settings to bake Lottie into `.gv`
+#[derive(Debug, Clone)]
+pub struct LottieBakeSettings {
+    /// render size (fit into, keeping aspect ratio). `None` = intrinsic size.
+    /// GV resolution is padded to multiple of 4 with transparent pixels.
+    pub size: Option<(u32, u32)>,
+    /// `None` = frame rate of the Lottie
+    pub fps: Option<f32>,
+    /// use `DXT5` or `BC7` for smooth alpha, `DXT1` has 1-bit alpha
+    pub format: GVFormat,
+}
+
+impl Default for LottieBakeSettings {
+    fn default() -> Self {
+        Self {
+            size: None,
+            fps: None,
+            format: GVFormat::DXT5,
+        }
+    }
+}
+
+/// Render all frames of Lottie and write them as `.gv` (straight alpha), so heavy animations can be played by `GVMoviePlayer`.
+///
+/// Same as `encode_movie_to_gv` with the render size. The render size and the playing state of the player are kept.
+/// `on_progress` is called with (done, total) frames.
+pub fn bake_lottie_to_gv<W, F>(
+    player: &mut LottieMoviePlayer,
+    writer: W,
+    settings: &LottieBakeSettings,
+    on_progress: F,
+) -> std::io::Result<W>
+    where
+        W: Write + Seek,
+        F: FnMut(u32, u32),
+{
+    let (width, height) = player.get_resolution();
+    if let Some((bake_width, bake_height)) = settings.size {
+        player.set_render_size(bake_width, bake_height);
+    }
+    let encode_settings = GVEncodeSettings {
+        fps: settings.fps,
+        format: settings.format,
+    };
+    let result = encode_movie_to_gv(player, writer, &encode_settings, on_progress);
+
+    // restore the surface as is (`set_render_size` fits the size again, which may round differently)
+    if player.get_resolution() != (width, height) {
+        player.lottie_surface = rlottie::Surface::new(rlottie::Size {
+            width: width as usize,
+            height: height as usize,
+        });
+    }
+    result
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lottie::load_lottie_from_data;
+    use gv_video::GVVideo;
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    #[test]
+    fn bake() {
+        let json = r#"{"v":"5.7.0","fr":10,"ip":0,"op":10,"w":6,"h":6,"layers":[
+            {"ty":4,"nm":"Box","ip":0,"op":10,"st":0,"ks":{},"shapes":[
+                {"ty":"rc","nm":"Rect","p":{"a":0,"k":[3,3]},"s":{"a":0,"k":[6,6]},"r":{"a":0,"k":0}},
+                {"ty":"fl","nm":"Fill 1","c":{"a":0,"k":[1,0,0,1]},"o":{"a":0,"k":100}}
+            ]}]}"#;
+        let mut player = load_lottie_from_data(json, "bake", "");
+        let mut progress = vec![];
+        let settings = LottieBakeSettings {
+            fps: Some(5.0),
+            format: GVFormat::DXT5,
+            ..Default::default()
+        };
+        let bytes = bake_lottie_to_gv(&mut player, Cursor::new(Vec::new()), &settings, |done, total| progress.push((done, total)))
+            .unwrap()
+            .into_inner();
+        assert_eq!(progress.last(), Some(&(5, 5)));
+
+        let mut gv = GVVideo::load(Cursor::new(bytes));
+        assert_eq!(gv.get_resolution(), (8, 8));
+        assert_eq!(gv.get_frame_count(), 5);
+        let frame = gv_video::get_bgra_vec_from_frame(gv.read_frame(0).unwrap());
+        // red inside, transparent padding
+        assert!(frame[2] > 240 && frame[3] > 240);
+        assert_eq!(frame[(7 * 8 + 7) * 4 + 3], 0);
+
+        // baked at another size, the render size of the player is kept
+        let settings = LottieBakeSettings {
+            size: Some((12, 12)),
+            ..settings
+        };
+        let bytes = bake_lottie_to_gv(&mut player, Cursor::new(Vec::new()), &settings, |_, _| {})
+            .unwrap()
+            .into_inner();
+        assert_eq!(GVVideo::load(Cursor::new(bytes)).get_resolution(), (12, 12));
+        assert_eq!(player.get_resolution(), (6, 6));
+    }
+
+    #[test]
+    fn encode_player() {
+        // same result through the generic encoder
+        let json = r#"{"v":"5.7.0","fr":10,"ip":0,"op":10,"w":4,"h":4,"layers":[]}"#;
+        let mut player = load_lottie_from_data(json, "encode_player", "");
+        let settings = crate::gv_encode::GVEncodeSettings::default();
+        let bytes = crate::gv_encode::encode_movie_to_gv(&mut player, Cursor::new(Vec::new()), &settings, |_, _| {})
+            .unwrap()
+            .into_inner();
+        let gv = GVVideo::load(Cursor::new(bytes));
+        assert_eq!(gv.get_frame_count(), 10);
+        assert_eq!(gv.get_fps(), 10.0);
+        // the player is untouched
+        assert_eq!(player.get_state(), crate::movie_player::PlayingState::Stopped);
+        assert_eq!(player.get_position(), Duration::ZERO);
+    }
+}
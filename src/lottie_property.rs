@@ -0,0 +1,267 @@
+use bevy::color::Color;
+use bevy::color::ColorToComponents;
+use bevy::color::Srgba;
+use bevy::math::Vec2;
+use serde_json::json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+/// Property which can be overridden at runtime by keypath.
+///
+/// Values are static (animated keyframes are replaced), same as rlottie's property override.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LottieProperty {
+    FillColor(Color),
+    /// 0.0 - 100.0
+    FillOpacity(f32),
+    StrokeColor(Color),
+    /// 0.0 - 100.0
+    StrokeOpacity(f32),
+    StrokeWidth(f32),
+    /// transform of layer or group, 0.0 - 100.0
+    TransformOpacity(f32),
+    TransformPosition(Vec2),
+    /// percent (100.0 = original size)
+    TransformScale(Vec2),
+    /// degrees
+    TransformRotation(f32),
+}
+
+/// Match keypath like `Layer.Group.Fill 1`, `**.Fill 1` or `Layer.*.Stroke 1`.
+///
+/// `*` matches one name, `**` matches zero or more names.
+pub fn keypath_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            keypath_matches(&pattern[1..], path)
+                || (!path.is_empty() && keypath_matches(pattern, &path[1..]))
+        },
+        (Some(&"*"), Some(_)) => keypath_matches(&pattern[1..], &path[1..]),
+        (Some(p), Some(name)) if p == name => keypath_matches(&pattern[1..], &path[1..]),
+        _ => false,
+    }
+}
+
+fn static_value(value: Value) -> Value {
+    json!({ "a": 0, "k": value })
+}
+
+fn color_value(color: &Color) -> Value {
+    let [r, g, b, a] = Srgba::from(*color).to_f32_array();
+    static_value(json!([r, g, b, a]))
+}
+
+fn name_of(value: &Value) -> &str {
+    value.get("nm").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Position of split transforms (`"s": true` with `x` / `y`, or `px` / `py` of older exporters)
+fn apply_split_position(transform: &mut Value, position: Vec2) -> bool {
+    let Some(obj) = transform.as_object_mut() else {
+        return false;
+    };
+    if obj.contains_key("px") || obj.contains_key("py") {
+        obj.insert("px".to_string(), static_value(json!(position.x)));
+        obj.insert("py".to_string(), static_value(json!(position.y)));
+        return true;
+    }
+    match obj.get_mut("p").and_then(|p| p.as_object_mut()) {
+        Some(p) if p.get("s").and_then(|s| s.as_bool()) == Some(true) => {
+            p.insert("x".to_string(), static_value(json!(position.x)));
+            p.insert("y".to_string(), static_value(json!(position.y)));
+            true
+        },
+        _ => false,
+    }
+}
+
+fn apply_transform(transform: &mut Value, property: &LottieProperty) {
+    if let LottieProperty::TransformPosition(p) = property {
+        if apply_split_position(transform, *p) {
+            return;
+        }
+    }
+    let (key, value) = match property {
+        LottieProperty::TransformOpacity(o) => ("o", static_value(json!(o))),
+        LottieProperty::TransformPosition(p) => ("p", static_value(json!([p.x, p.y]))),
+        LottieProperty::TransformScale(s) => ("s", static_value(json!([s.x, s.y]))),
+        LottieProperty::TransformRotation(r) => ("r", static_value(json!(r))),
+        _ => return,
+    };
+    if let Some(obj) = transform.as_object_mut() {
+        obj.insert(key.to_string(), value);
+    }
+}
+
+fn apply_to_shapes(shapes: &mut [Value], path: &mut Vec<String>, pattern: &[&str], property: &LottieProperty) {
+    for shape in shapes.iter_mut() {
+        let ty = shape.get("ty").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        path.push(name_of(shape).to_string());
+        let path_refs: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+
+        match ty.as_str() {
+            "gr" => {
+                if let Some(items) = shape.get_mut("it").and_then(|v| v.as_array_mut()) {
+                    for item in items.iter_mut() {
+                        // group transform belongs to the group keypath
+                        if item.get("ty").and_then(|v| v.as_str()) == Some("tr") && keypath_matches(pattern, &path_refs) {
+                            apply_transform(item, property);
+                        }
+                    }
+                    apply_to_shapes(items, path, pattern, property);
+                }
+            },
+            "fl" if keypath_matches(pattern, &path_refs) => {
+                if let Some(obj) = shape.as_object_mut() {
+                    match property {
+                        LottieProperty::FillColor(c) => { obj.insert("c".to_string(), color_value(c)); },
+                        LottieProperty::FillOpacity(o) => { obj.insert("o".to_string(), static_value(json!(o))); },
+                        _ => {},
+                    }
+                }
+            },
+            "st" if keypath_matches(pattern, &path_refs) => {
+                if let Some(obj) = shape.as_object_mut() {
+                    match property {
+                        LottieProperty::StrokeColor(c) => { obj.insert("c".to_string(), color_value(c)); },
+                        LottieProperty::StrokeOpacity(o) => { obj.insert("o".to_string(), static_value(json!(o))); },
+                        LottieProperty::StrokeWidth(w) => { obj.insert("w".to_string(), static_value(json!(w))); },
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+        path.pop();
+    }
+}
+
+fn apply_to_layers(layers: &mut [Value], assets: &HashMap<String, Vec<Value>>, path: &mut Vec<String>, pattern: &[&str], property: &LottieProperty) {
+    for layer in layers.iter_mut() {
+        path.push(name_of(layer).to_string());
+        let path_refs: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+
+        if keypath_matches(pattern, &path_refs) {
+            if let Some(transform) = layer.get_mut("ks") {
+                apply_transform(transform, property);
+            }
+        }
+        if let Some(shapes) = layer.get_mut("shapes").and_then(|v| v.as_array_mut()) {
+            apply_to_shapes(shapes, path, pattern, property);
+        }
+        // precomp layer: apply to a copy of its layers, and embed them to keep other references intact
+        let ref_id = layer.get("refId").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if let Some(original) = assets.get(&ref_id) {
+            let mut precomp_layers = original.clone();
+            apply_to_layers(&mut precomp_layers, assets, path, pattern, property);
+            if &precomp_layers != original {
+                if let Some(obj) = layer.as_object_mut() {
+                    obj.insert("refId".to_string(), json!(format!("{}#{}", ref_id, path.join("."))));
+                    obj.insert("__precomp_layers".to_string(), json!(precomp_layers));
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+/// Move `__precomp_layers` of precomp layers into `assets`
+fn collect_precomps(layers: &mut [Value], new_assets: &mut Vec<Value>) {
+    for layer in layers.iter_mut() {
+        let Some(obj) = layer.as_object_mut() else {
+            continue;
+        };
+        if let Some(Value::Array(mut precomp_layers)) = obj.remove("__precomp_layers") {
+            collect_precomps(&mut precomp_layers, new_assets);
+            let id = obj.get("refId").cloned().unwrap_or(Value::Null);
+            new_assets.push(json!({ "id": id, "layers": precomp_layers }));
+        }
+    }
+}
+
+/// Apply property overrides (in order) to Lottie JSON
+pub fn apply_properties(json: &mut Value, overrides: &[(String, LottieProperty)]) {
+    for (keypath, property) in overrides {
+        let pattern: Vec<&str> = keypath.split('.').collect();
+
+        let assets: HashMap<String, Vec<Value>> = json.get("assets")
+            .and_then(|v| v.as_array())
+            .map(|assets| assets.iter()
+                .filter_map(|asset| Some((
+                    asset.get("id")?.as_str()?.to_string(),
+                    asset.get("layers")?.as_array()?.clone(),
+                )))
+                .collect())
+            .unwrap_or_default();
+
+        let mut new_assets = vec![];
+        if let Some(layers) = json.get_mut("layers").and_then(|v| v.as_array_mut()) {
+            apply_to_layers(layers, &assets, &mut vec![], &pattern, property);
+            collect_precomps(layers, &mut new_assets);
+        }
+        if !new_assets.is_empty() {
+            if let Some(assets) = json.get_mut("assets").and_then(|v| v.as_array_mut()) {
+                assets.extend(new_assets);
+            }
+        }
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keypath() {
+        assert!(keypath_matches(&["**"], &["a", "b"]));
+        assert!(keypath_matches(&["**", "Fill 1"], &["Layer", "Group", "Fill 1"]));
+        assert!(keypath_matches(&["Layer", "*", "Fill 1"], &["Layer", "Group", "Fill 1"]));
+        assert!(!keypath_matches(&["Layer", "*", "Fill 1"], &["Layer", "Fill 1"]));
+        assert!(!keypath_matches(&["Other", "**"], &["Layer", "Fill 1"]));
+    }
+
+    #[test]
+    fn override_fill_color() {
+        let mut json: Value = serde_json::from_str(r#"{"layers":[{"nm":"Icon","ks":{},"shapes":[
+            {"ty":"gr","nm":"Body","it":[
+                {"ty":"fl","nm":"Fill 1","c":{"a":1,"k":[]}},
+                {"ty":"st","nm":"Stroke 1","c":{"a":0,"k":[0,0,0,1]},"w":{"a":0,"k":1}},
+                {"ty":"tr","o":{"a":0,"k":100}}
+            ]}
+        ]}]}"#).unwrap();
+
+        apply_properties(&mut json, &[
+            ("**.Fill 1".to_string(), LottieProperty::FillColor(Color::srgb(1.0, 0.0, 0.0))),
+            ("Icon.Body.Stroke 1".to_string(), LottieProperty::StrokeWidth(4.0)),
+            ("Icon.Body".to_string(), LottieProperty::TransformOpacity(50.0)),
+            ("Icon".to_string(), LottieProperty::TransformRotation(90.0)),
+        ]);
+
+        let items = &json["layers"][0]["shapes"][0]["it"];
+        assert_eq!(items[0]["c"], json!({"a": 0, "k": [1.0, 0.0, 0.0, 1.0]}));
+        assert_eq!(items[1]["w"], json!({"a": 0, "k": 4.0}));
+        assert_eq!(items[2]["o"], json!({"a": 0, "k": 50.0}));
+        assert_eq!(json["layers"][0]["ks"]["r"], json!({"a": 0, "k": 90.0}));
+    }
+
+    #[test]
+    fn override_split_position() {
+        let mut json: Value = serde_json::from_str(r#"{"layers":[
+            {"nm":"Split","ks":{"p":{"s":true,"x":{"a":1,"k":[]},"y":{"a":0,"k":0}}}},
+            {"nm":"Old","ks":{"px":{"a":0,"k":0},"py":{"a":0,"k":0}}},
+            {"nm":"Plain","ks":{"p":{"a":0,"k":[0,0]}}}
+        ]}"#).unwrap();
+
+        apply_properties(&mut json, &[("*".to_string(), LottieProperty::TransformPosition(Vec2::new(1.0, 2.0)))]);
+
+        let layers = &json["layers"];
+        assert_eq!(layers[0]["ks"]["p"], json!({"s": true, "x": {"a": 0, "k": 1.0}, "y": {"a": 0, "k": 2.0}}));
+        assert_eq!(layers[1]["ks"]["px"], json!({"a": 0, "k": 1.0}));
+        assert_eq!(layers[1]["ks"]["py"], json!({"a": 0, "k": 2.0}));
+        assert!(layers[1]["ks"].get("p").is_none());
+        assert_eq!(layers[2]["ks"]["p"], json!({"a": 0, "k": [1.0, 2.0]}));
+    }
+}
@@ -0,0 +1,320 @@
+use ab_glyph::Font as _;
+use ab_glyph::FontRef;
+use ab_glyph::OutlineCurve;
+use ab_glyph::Point;
+use bevy::log::warn;
+use serde_json::json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Text layer (`ty: 5`) in Lottie JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct LottieTextLayer {
+    /// `nm` of the layer
+    pub name: String,
+    /// text of the first keyframe
+    pub text: String,
+    /// `f` (`fName` in `fonts.list`)
+    pub font_name: String,
+    /// `fFamily` in `fonts.list`
+    pub font_family: Option<String>,
+    pub font_size: f32,
+}
+
+/// Fonts used to render text layers, by font name (`fName`) or family (`fFamily`)
+#[derive(Debug, Clone, Default)]
+pub struct LottieFontMap {
+    /// (font data, face index)
+    fonts: HashMap<String, (Arc<Vec<u8>>, u32)>,
+}
+
+impl LottieFontMap {
+    pub fn insert(&mut self, name: &str, data: Arc<Vec<u8>>, face_index: u32) {
+        self.fonts.insert(name.to_string(), (data, face_index));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.fonts.remove(name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+
+    /// Find a system font by family name (e.g. `Noto Sans JP`) and map it to `name`.
+    ///
+    /// NOTE: scans system font directories, so don't call it every frame.
+    pub fn insert_system_font(&mut self, name: &str, family: &str) -> bool {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let query = fontdb::Query {
+            families: &[fontdb::Family::Name(family)],
+            ..Default::default()
+        };
+        let Some(id) = db.query(&query) else {
+            return false;
+        };
+        let Some((data, face_index)) = db.with_face_data(id, |data, face_index| (data.to_vec(), face_index)) else {
+            return false;
+        };
+        self.insert(name, Arc::new(data), face_index);
+        true
+    }
+
+    fn get(&self, name: &str) -> Option<FontRef<'_>> {
+        let (data, face_index) = self.fonts.get(name)?;
+        FontRef::try_from_slice_and_index(data, *face_index).ok()
+    }
+}
+
+fn for_each_layer<F: FnMut(&mut Value)>(json: &mut Value, mut f: F) {
+    if let Some(layers) = json.get_mut("layers").and_then(|v| v.as_array_mut()) {
+        layers.iter_mut().for_each(&mut f);
+    }
+    if let Some(assets) = json.get_mut("assets").and_then(|v| v.as_array_mut()) {
+        for asset in assets.iter_mut() {
+            if let Some(layers) = asset.get_mut("layers").and_then(|v| v.as_array_mut()) {
+                layers.iter_mut().for_each(&mut f);
+            }
+        }
+    }
+}
+
+/// `fName` -> `fFamily`
+fn get_font_families(json: &Value) -> HashMap<String, String> {
+    json.pointer("/fonts/list")
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter()
+            .filter_map(|font| Some((
+                font.get("fName")?.as_str()?.to_string(),
+                font.get("fFamily")?.as_str()?.to_string(),
+            )))
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Text document of the first keyframe (`t.d.k[0].s`)
+fn get_text_document(layer: &Value) -> Option<&Value> {
+    layer.pointer("/t/d/k/0/s")
+}
+
+/// Text layers in root and precomps
+pub fn get_text_layers(json: &Value) -> Vec<LottieTextLayer> {
+    let families = get_font_families(json);
+    let mut json = json.clone();
+    let mut text_layers = vec![];
+    for_each_layer(&mut json, |layer| {
+        if layer.get("ty").and_then(|v| v.as_i64()) != Some(5) {
+            return;
+        }
+        let Some(document) = get_text_document(layer) else {
+            return;
+        };
+        let font_name = document.get("f").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        text_layers.push(LottieTextLayer {
+            name: layer.get("nm").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            text: document.get("t").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            font_family: families.get(&font_name).cloned(),
+            font_name,
+            font_size: document.get("s").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32,
+        });
+    });
+    text_layers
+}
+
+/// Builds Lottie path vertices (`v`, `i`, `o`) of contours from glyph outlines
+#[derive(Default)]
+struct PathBuilder {
+    paths: Vec<Value>,
+    vertices: Vec<[f32; 2]>,
+    in_tangents: Vec<[f32; 2]>,
+    out_tangents: Vec<[f32; 2]>,
+}
+
+impl PathBuilder {
+    fn close(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        // last vertex is the same as the first one on closed contours
+        if self.vertices.len() > 1 && self.vertices.first() == self.vertices.last() {
+            self.vertices.pop();
+            self.in_tangents[0] = self.in_tangents.pop().unwrap();
+            self.out_tangents.pop();
+        }
+        self.paths.push(json!({
+            "ty": "sh",
+            "ks": { "a": 0, "k": {
+                "c": true,
+                "v": std::mem::take(&mut self.vertices),
+                "i": std::mem::take(&mut self.in_tangents),
+                "o": std::mem::take(&mut self.out_tangents),
+            }},
+        }));
+    }
+
+    /// cubic segment from the last vertex to `p3`
+    fn cubic_to(&mut self, p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], p3: [f32; 2]) {
+        if self.vertices.last() != Some(&p0) {
+            self.close();
+            self.vertices.push(p0);
+            self.in_tangents.push([0.0, 0.0]);
+            self.out_tangents.push([0.0, 0.0]);
+        }
+        *self.out_tangents.last_mut().unwrap() = [p1[0] - p0[0], p1[1] - p0[1]];
+        self.vertices.push(p3);
+        self.in_tangents.push([p2[0] - p3[0], p2[1] - p3[1]]);
+        self.out_tangents.push([0.0, 0.0]);
+    }
+}
+
+/// Layout text with the font and convert glyph outlines into Lottie paths (origin at baseline of the first line)
+fn build_text_paths(font: &FontRef, document: &Value, text: &str) -> Vec<Value> {
+    let size = document.get("s").and_then(|v| v.as_f64()).unwrap_or(12.0) as f32;
+    let line_height = document.get("lh").and_then(|v| v.as_f64()).map(|v| v as f32).unwrap_or(size * 1.2);
+    let tracking = document.get("tr").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32 / 1000.0 * size;
+    let justify = document.get("j").and_then(|v| v.as_i64()).unwrap_or(0);
+    let scale = size / font.units_per_em().unwrap_or(1000.0);
+
+    let mut builder = PathBuilder::default();
+    // NOTE: Lottie uses '\r' as line break
+    for (line_index, line) in text.split(['\r', '\n']).enumerate() {
+        let glyphs: Vec<_> = line.chars().map(|c| font.glyph_id(c)).collect();
+        let mut positions = vec![];
+        let mut x = 0.0;
+        for (i, &glyph) in glyphs.iter().enumerate() {
+            if i > 0 {
+                x += font.kern_unscaled(glyphs[i - 1], glyph) * scale + tracking;
+            }
+            positions.push(x);
+            x += font.h_advance_unscaled(glyph) * scale;
+        }
+        let offset_x = match justify {
+            1 => -x,
+            2 => -x / 2.0,
+            _ => 0.0,
+        };
+        let offset_y = line_index as f32 * line_height;
+
+        for (&glyph, &glyph_x) in glyphs.iter().zip(&positions) {
+            let Some(outline) = font.outline(glyph) else {
+                continue;
+            };
+            // font units (y up) to layer pixels (y down)
+            let to_px = |p: Point| [offset_x + glyph_x + p.x * scale, offset_y - p.y * scale];
+            let lerp = |a: [f32; 2], b: [f32; 2], t: f32| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+            for curve in outline.curves {
+                match curve {
+                    OutlineCurve::Line(p0, p1) => {
+                        let (p0, p1) = (to_px(p0), to_px(p1));
+                        builder.cubic_to(p0, p0, p1, p1);
+                    },
+                    OutlineCurve::Quad(p0, p1, p2) => {
+                        let (p0, p1, p2) = (to_px(p0), to_px(p1), to_px(p2));
+                        builder.cubic_to(p0, lerp(p0, p1, 2.0 / 3.0), lerp(p2, p1, 2.0 / 3.0), p2);
+                    },
+                    OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                        builder.cubic_to(to_px(p0), to_px(p1), to_px(p2), to_px(p3));
+                    },
+                }
+            }
+            builder.close();
+        }
+    }
+    builder.close();
+    builder.paths
+}
+
+fn static_value(value: Value) -> Value {
+    json!({ "a": 0, "k": value })
+}
+
+/// Replace text (by layer name), and convert text layers into shape layers with the mapped fonts.
+///
+/// rlottie doesn't render text layers, so text layers without mapped font stay invisible.
+pub fn convert_text_layers(json: &mut Value, texts: &[(String, String)], fonts: &LottieFontMap) {
+    let families = get_font_families(json);
+    for_each_layer(json, |layer| {
+        if layer.get("ty").and_then(|v| v.as_i64()) != Some(5) {
+            return;
+        }
+        let name = layer.get("nm").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let Some(document) = get_text_document(layer).cloned() else {
+            return;
+        };
+        let font_name = document.get("f").and_then(|v| v.as_str()).unwrap_or("");
+        let font = fonts.get(font_name)
+            .or_else(|| families.get(font_name).and_then(|family| fonts.get(family)));
+        let Some(font) = font else {
+            warn!("No font is mapped for Lottie text layer {} ({})", name, font_name);
+            return;
+        };
+
+        let text = texts.iter().rev()
+            .find(|(layer_name, _)| *layer_name == name)
+            .map(|(_, text)| text.clone())
+            .unwrap_or_else(|| document.get("t").and_then(|v| v.as_str()).unwrap_or("").to_string());
+
+        let mut items = build_text_paths(&font, &document, &text);
+        let color = document.get("fc").cloned().unwrap_or(json!([0, 0, 0]));
+        items.push(json!({ "ty": "fl", "nm": "Fill 1", "c": static_value(color), "o": static_value(json!(100)), "r": 1 }));
+        if let (Some(stroke_color), Some(stroke_width)) = (document.get("sc"), document.get("sw")) {
+            items.push(json!({ "ty": "st", "nm": "Stroke 1", "c": static_value(stroke_color.clone()), "o": static_value(json!(100)), "w": static_value(stroke_width.clone()), "lc": 2, "lj": 2 }));
+        }
+        items.push(json!({
+            "ty": "tr",
+            "p": static_value(json!([0, 0])),
+            "a": static_value(json!([0, 0])),
+            "s": static_value(json!([100, 100])),
+            "r": static_value(json!(0)),
+            "o": static_value(json!(100)),
+        }));
+
+        if let Some(obj) = layer.as_object_mut() {
+            obj.remove("t");
+            obj.insert("ty".to_string(), json!(4));
+            obj.insert("shapes".to_string(), json!([{ "ty": "gr", "nm": "Text", "it": items }]));
+        }
+    });
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"v":"5.7.0","fr":30,"ip":0,"op":30,"w":200,"h":50,
+        "fonts":{"list":[{"fName":"DejaVuSans","fFamily":"DejaVu Sans","fStyle":"Book"}]},
+        "layers":[{"ty":5,"nm":"Title","ip":0,"op":30,"st":0,"ks":{},
+            "t":{"d":{"k":[{"s":{"s":24,"f":"DejaVuSans","t":"Hello","j":2,"fc":[1,1,1]},"t":0}]}}}]}"#;
+
+    #[test]
+    fn text_layers() {
+        let json: Value = serde_json::from_str(JSON).unwrap();
+        let layers = get_text_layers(&json);
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].name, "Title");
+        assert_eq!(layers[0].text, "Hello");
+        assert_eq!(layers[0].font_family, Some("DejaVu Sans".to_string()));
+        assert_eq!(layers[0].font_size, 24.0);
+    }
+
+    #[test]
+    fn convert_with_system_font() {
+        let mut fonts = LottieFontMap::default();
+        if !fonts.insert_system_font("DejaVu Sans", "DejaVu Sans") {
+            eprintln!("DejaVu Sans not found, skipped");
+            return;
+        }
+        let mut json: Value = serde_json::from_str(JSON).unwrap();
+        convert_text_layers(&mut json, &[("Title".to_string(), "Hi".to_string())], &fonts);
+
+        let layer = &json["layers"][0];
+        assert_eq!(layer["ty"], 4);
+        let items = layer["shapes"][0]["it"].as_array().unwrap();
+        // "H" has 1 contour, "i" has 2
+        assert_eq!(items.iter().filter(|item| item["ty"] == "sh").count(), 3);
+    }
+}
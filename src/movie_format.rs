@@ -0,0 +1,374 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::AssetSourceId;
+use bevy::asset::io::Reader;
+use bevy::asset::AssetLoader;
+use bevy::asset::AssetPath;
+use bevy::asset::LoadContext;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+
+#[cfg(feature = "ffmpeg")]
+use crate::ffmpeg::FFmpegMovie;
+#[cfg(feature = "gv")]
+use crate::gv::GVMovie;
+#[cfg(feature = "lottie")]
+use crate::lottie::LottieMovie;
+use crate::gv_header::GVHeader;
+
+use std::path::PathBuf;
+
+/// Movie backend detected from file content (magic bytes).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MovieFormat {
+    GV,
+    Lottie,
+    FFmpeg,
+}
+
+impl MovieFormat {
+    /// Detect backend from the head of the file. Returns `None` if unknown.
+    pub fn detect(bytes: &[u8]) -> Option<MovieFormat> {
+        // NOTE: GV has no magic number, but its header is strict enough (frame bytes must match),
+        // and it must be checked before weak magics like mpeg-ts sync bytes
+        if is_gv(bytes) {
+            Some(MovieFormat::GV)
+        } else if is_ffmpeg_container(bytes) {
+            Some(MovieFormat::FFmpeg)
+        } else if is_lottie_json(bytes) {
+            Some(MovieFormat::Lottie)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_ffmpeg_container(bytes: &[u8]) -> bool {
+    let starts_with = |magic: &[u8]| bytes.starts_with(magic);
+
+    // mp4 / mov / m4v / 3gp
+    if bytes.len() >= 8 && matches!(&bytes[4..8], b"ftyp" | b"moov" | b"mdat" | b"free" | b"wide" | b"skip") {
+        return true;
+    }
+    // avi
+    if bytes.len() >= 12 && starts_with(b"RIFF") && &bytes[8..12] == b"AVI " {
+        return true;
+    }
+    // mpeg-ts (sync byte every 188 bytes)
+    if bytes.len() > 188 && bytes[0] == 0x47 && bytes[188] == 0x47 {
+        return true;
+    }
+
+    starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) // mkv / webm
+        || starts_with(b"OggS") // ogv
+        || starts_with(b"FLV") // flv
+        || starts_with(&[0x06, 0x0E, 0x2B, 0x34]) // mxf
+        || starts_with(&[0x00, 0x00, 0x01, 0xBA]) // mpeg-ps
+        || starts_with(b"GIF87a")
+        || starts_with(b"GIF89a")
+}
+
+fn is_lottie_json(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(4096)];
+    let first = head.iter().find(|b| !b.is_ascii_whitespace());
+    if first != Some(&b'{') {
+        return false;
+    }
+    let contains = |needle: &[u8]| head.windows(needle.len()).any(|w| w == needle);
+    contains(b"\"v\"") && (contains(b"\"fr\"") || contains(b"\"layers\""))
+}
+
+fn is_gv(bytes: &[u8]) -> bool {
+    GVHeader::parse(bytes).is_some_and(|header| header.check().is_ok())
+}
+
+/// Extensions claimed by each backend loader.
+///
+/// Extensions listed by more than one backend are treated as ambiguous,
+/// and handled by [`AnyMovieLoader`] with content sniffing.
+///
+/// Insert as a resource before adding `MoviePlayerPlugin` to change them.
+#[derive(Resource, Debug, Clone)]
+pub struct MovieExtensions {
+    pub gv: Vec<&'static str>,
+    pub lottie: Vec<&'static str>,
+    pub ffmpeg: Vec<&'static str>,
+}
+
+impl Default for MovieExtensions {
+    fn default() -> Self {
+        Self {
+            gv: default_gv_extensions(),
+            lottie: default_lottie_extensions(),
+            ffmpeg: default_ffmpeg_extensions(),
+        }
+    }
+}
+
+pub fn default_gv_extensions() -> Vec<&'static str> {
+    vec!["gv"]
+}
+
+pub fn default_lottie_extensions() -> Vec<&'static str> {
+    #[cfg(not(feature = "disable-json-extension-for-lottie"))]
+    {
+        vec!["json", "lottie.json"]
+    }
+
+    #[cfg(feature = "disable-json-extension-for-lottie")]
+    {
+        vec!["lottie.json"]
+    }
+}
+
+pub fn default_ffmpeg_extensions() -> Vec<&'static str> {
+    vec!["mp4", "mov", "avi", "webm", "mkv", "ogv", "m4v"]
+}
+
+impl MovieExtensions {
+    /// No extensions for any backend
+    pub fn empty() -> Self {
+        Self {
+            gv: vec![],
+            lottie: vec![],
+            ffmpeg: vec![],
+        }
+    }
+
+    pub fn with_gv(mut self, extensions: &[&'static str]) -> Self {
+        self.gv = extensions.to_vec();
+        self
+    }
+
+    pub fn with_lottie(mut self, extensions: &[&'static str]) -> Self {
+        self.lottie = extensions.to_vec();
+        self
+    }
+
+    pub fn with_ffmpeg(mut self, extensions: &[&'static str]) -> Self {
+        self.ffmpeg = extensions.to_vec();
+        self
+    }
+
+    pub fn add_gv(mut self, extension: &'static str) -> Self {
+        self.gv.push(extension);
+        self
+    }
+
+    pub fn add_lottie(mut self, extension: &'static str) -> Self {
+        self.lottie.push(extension);
+        self
+    }
+
+    pub fn add_ffmpeg(mut self, extension: &'static str) -> Self {
+        self.ffmpeg.push(extension);
+        self
+    }
+
+    /// Extensions claimed by two or more enabled backends
+    pub fn ambiguous(&self) -> Vec<&'static str> {
+        let lists: Vec<&Vec<&'static str>> = [
+            (cfg!(feature = "gv"), &self.gv),
+            (cfg!(feature = "lottie"), &self.lottie),
+            (cfg!(feature = "ffmpeg"), &self.ffmpeg),
+        ].into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, list)| list)
+            .collect();
+
+        let mut result: Vec<&'static str> = vec![];
+        for (i, list) in lists.iter().enumerate() {
+            for ext in list.iter() {
+                let claimed_by_other = lists.iter().skip(i + 1).any(|other| other.contains(ext));
+                if claimed_by_other && !result.contains(ext) {
+                    result.push(ext);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Directory of the default asset source on disk, for backends which open files directly
+/// (ffmpeg, memory-mapped GV). Default is `AssetPlugin::file_path` under the bevy base path.
+///
+/// Insert as a resource before adding `MoviePlayerPlugin` if `AssetPlugin::file_path` is changed.
+#[derive(Resource, Debug, Clone)]
+pub struct MovieAssetRoot(pub PathBuf);
+
+impl Default for MovieAssetRoot {
+    fn default() -> Self {
+        Self::from_file_path(&AssetPlugin::default().file_path)
+    }
+}
+
+impl MovieAssetRoot {
+    /// Same as `AssetPlugin::file_path` (relative to `BEVY_ASSET_ROOT`, `CARGO_MANIFEST_DIR` or the executable)
+    pub fn from_file_path(file_path: &str) -> Self {
+        Self(FileAssetReader::get_base_path().join(file_path))
+    }
+
+    /// File of an asset. Only assets of the default source are files under this directory
+    pub fn resolve(&self, path: &AssetPath) -> std::io::Result<PathBuf> {
+        if *path.source() != AssetSourceId::Default {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} is not a file of the default asset source", path),
+            ));
+        }
+        let file = self.0.join(path.path());
+        if !file.is_file() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} is not found at {}", path, file.display()),
+            ));
+        }
+        Ok(file)
+    }
+}
+
+/// Movie asset whose backend was decided by content sniffing.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub enum AnyMovie {
+    #[cfg(feature = "gv")]
+    GV(#[dependency] Handle<GVMovie>),
+    #[cfg(feature = "lottie")]
+    Lottie(#[dependency] Handle<LottieMovie>),
+    #[cfg(feature = "ffmpeg")]
+    FFmpeg(#[dependency] Handle<FFmpegMovie>),
+}
+
+/// Loader for ambiguous extensions, which sniffs magic bytes and
+/// loads the movie as a labeled sub asset of the detected backend.
+#[derive(TypePath)]
+pub struct AnyMovieLoader {
+    extensions: Vec<&'static str>,
+    /// for backends which open files directly (set by `MoviePlayerPlugin`)
+    asset_root: MovieAssetRoot,
+}
+
+impl AnyMovieLoader {
+    pub fn with_extensions(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            asset_root: MovieAssetRoot::default(),
+        }
+    }
+
+    pub fn with_asset_root(mut self, asset_root: MovieAssetRoot) -> Self {
+        self.asset_root = asset_root;
+        self
+    }
+}
+
+impl AssetLoader for AnyMovieLoader {
+    type Asset = AnyMovie;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        #[cfg_attr(not(feature = "ffmpeg"), allow(unused_variables))]
+        let asset_root = self.asset_root.clone();
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let format = MovieFormat::detect(&bytes).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("could not detect movie format: {}", load_context.path()),
+                )
+            })?;
+
+            match format {
+                #[cfg(feature = "gv")]
+                MovieFormat::GV => {
+                    let reader = crate::gv::GVReader::OnMemory(std::io::Cursor::new(bytes));
+                    let player = crate::gv::try_load_gv_from_reader(reader)?;
+                    let handle = load_context.add_labeled_asset("gv".to_string(), GVMovie::from(player));
+                    Ok(AnyMovie::GV(handle))
+                },
+                #[cfg(feature = "lottie")]
+                MovieFormat::Lottie => {
+                    let player = crate::lottie::load_lottie_from_asset_bytes(bytes, load_context).await?;
+                    let handle = load_context.add_labeled_asset("lottie".to_string(), LottieMovie::from(player));
+                    Ok(AnyMovie::Lottie(handle))
+                },
+                #[cfg(feature = "ffmpeg")]
+                MovieFormat::FFmpeg => {
+                    let path = asset_root.resolve(load_context.path())?;
+                    let player = crate::ffmpeg::try_load_movie(&path)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                    let handle = load_context.add_labeled_asset("ffmpeg".to_string(), FFmpegMovie::from(player));
+                    Ok(AnyMovie::FFmpeg(handle))
+                },
+                #[allow(unreachable_patterns)]
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("{:?} backend is not enabled: {}", format, load_context.path()),
+                )),
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_format() {
+        let mut mp4 = vec![0, 0, 0, 0x18];
+        mp4.extend_from_slice(b"ftypmp42");
+        assert_eq!(MovieFormat::detect(&mp4), Some(MovieFormat::FFmpeg));
+        assert_eq!(MovieFormat::detect(&[0x1A, 0x45, 0xDF, 0xA3, 0x01]), Some(MovieFormat::FFmpeg));
+
+        let lottie = br#" {"v":"5.5.2","fr":30,"ip":0,"op":90,"layers":[]}"#;
+        assert_eq!(MovieFormat::detect(lottie), Some(MovieFormat::Lottie));
+
+        let mut gv = std::fs::read("assets/test.gv").unwrap();
+        assert_eq!(MovieFormat::detect(&gv), Some(MovieFormat::GV));
+        // mpeg-ts sync byte in frame data
+        gv[188] = 0x47;
+        assert_eq!(MovieFormat::detect(&gv), Some(MovieFormat::GV));
+
+        let mut ts = vec![0u8; 376];
+        ts[0] = 0x47;
+        ts[188] = 0x47;
+        assert_eq!(MovieFormat::detect(&ts), Some(MovieFormat::FFmpeg));
+
+        assert_eq!(MovieFormat::detect(b"hello"), None);
+    }
+
+    #[test]
+    fn asset_root() {
+        let root = MovieAssetRoot(std::path::PathBuf::from("assets"));
+        assert_eq!(root.resolve(&AssetPath::from("test.gv")).unwrap(), std::path::Path::new("assets/test.gv"));
+        let error = root.resolve(&AssetPath::from("not_found.gv")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        let error = root.resolve(&AssetPath::from("stream://udp/127.0.0.1:5000")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn ambiguous_extensions() {
+        let extensions = MovieExtensions::default()
+            .add_ffmpeg("ts")
+            .add_gv("ts");
+        let ambiguous = extensions.ambiguous();
+
+        #[cfg(all(feature = "gv", feature = "ffmpeg"))]
+        assert_eq!(ambiguous, vec!["ts"]);
+        #[cfg(not(all(feature = "gv", feature = "ffmpeg")))]
+        assert!(ambiguous.is_empty());
+    }
+}
@@ -1,13 +1,20 @@
 use bevy::{asset::AssetLoader, prelude::*, log::warn,
     render::{render_resource::{Extent3d, TextureDimension, TextureFormat}}};
 use bevy_asset::RenderAssetUsages;
+use serde::{Deserialize, Serialize};
 use std::{fmt, time::Duration};
 
-// #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-// pub enum LoadMode {
-//     OnMemory,
-//     DiskStream,
-// }
+/// How movie data is read (e.g. `GVLoaderSettings`)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum LoadMode {
+    /// read all data into memory
+    OnMemory,
+    /// seek + read per frame
+    #[default]
+    DiskStream,
+    /// memory-mapped file
+    MemoryMapped,
+}
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum PlayingState {
@@ -65,11 +72,74 @@ impl ImageData {
     }
 }
 
+/// Chapter of a movie (ffmpeg only)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovieChapter {
+    pub title: Option<String>,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+/// Container / stream metadata of a movie.
+///
+/// Fields are `None` (or empty) when the backend doesn't know them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MovieMetadata {
+    /// codec name (like `h264`, `DXT5`, `lottie`)
+    pub codec: Option<String>,
+    /// container name (like `mov,mp4,m4a,3gp,3g2,mj2`, `gv`, `json`)
+    pub container: Option<String>,
+    pub frame_rate: Option<f32>,
+    /// bits per second
+    pub bitrate: Option<u64>,
+    /// bits per color component
+    pub bit_depth: Option<u32>,
+    pub color_primaries: Option<String>,
+    pub color_transfer: Option<String>,
+    /// pixel aspect ratio (numerator, denominator)
+    pub pixel_aspect_ratio: Option<(i32, i32)>,
+    /// display matrix (3x3, 16.16 fixed point except last column), same as ffmpeg
+    pub display_matrix: Option<[i32; 9]>,
+    pub title: Option<String>,
+    pub tags: Vec<(String, String)>,
+    pub chapters: Vec<MovieChapter>,
+}
+
+impl MovieMetadata {
+    /// Counterclockwise rotation in degrees of the display matrix (0 if unknown).
+    /// Same as ffmpeg's `av_display_rotation_get()`, so rotate by the negated value to display upright.
+    pub fn get_rotation_degrees(&self) -> f64 {
+        if let Some(m) = self.display_matrix {
+            // same as av_display_rotation_get()
+            let scale_0 = (m[0] as f64).hypot(m[3] as f64);
+            let scale_1 = (m[1] as f64).hypot(m[4] as f64);
+            if scale_0 == 0.0 || scale_1 == 0.0 {
+                return 0.0;
+            }
+            let rotation = (m[1] as f64 / scale_1).atan2(m[0] as f64 / scale_0).to_degrees();
+            -rotation
+        } else {
+            0.0
+        }
+    }
+
+    /// Resolution after applying display rotation (swapped for 90 / 270 degrees)
+    pub fn get_display_resolution(&self, resolution: (u32, u32)) -> (u32, u32) {
+        let quarter_turns = (self.get_rotation_degrees() / 90.0).round() as i64;
+        if quarter_turns.rem_euclid(2) == 1 {
+            (resolution.1, resolution.0)
+        } else {
+            resolution
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SeekOutOfBoundsError {
     pub actual_seeked_position: Duration,
 }
 
+#[derive(Clone)]
 pub struct MoviePlayerStateController {
     pub state: PlayingState,
     pub bevy_elapsed_time: Duration,
@@ -77,6 +147,8 @@ pub struct MoviePlayerStateController {
     pub pause_started_time: Option<Duration>,
     pub seek_position: Duration,
     pub loop_mode: LoopMode,
+    /// (start, end) used as loop range instead of the whole movie
+    pub play_range: Option<(Duration, Duration)>,
 }
 
 impl Default for MoviePlayerStateController {
@@ -88,6 +160,7 @@ impl Default for MoviePlayerStateController {
             pause_started_time: None,
             seek_position: Duration::from_secs(0),
             loop_mode: LoopMode::default(),
+            play_range: None,
         }
     }
 }
@@ -178,17 +251,18 @@ impl MoviePlayerStateController {
 
         if self.state == PlayingState::Playing {
             let position = self.get_position(bevy_elapsed_time);
-            if position >= duration {
+            let (range_start, range_end) = self.play_range.unwrap_or((Duration::from_secs(0), duration));
+            if position >= range_end {
                 match self.loop_mode {
                     LoopMode::Stop => {
                         self.stop();
                     },
                     LoopMode::Loop => {
-                        let _ = self.seek(Duration::from_secs(0), bevy_elapsed_time, duration);
+                        let _ = self.seek(range_start, bevy_elapsed_time, duration);
                     },
                     LoopMode::PauseAtEnd => {
                         // WORKAROUND: seek to the end - 0.1ms
-                        let _ = self.seek(duration - Duration::from_secs_f32(0.0001), bevy_elapsed_time, duration);
+                        let _ = self.seek(range_end.saturating_sub(Duration::from_secs_f32(0.0001)), bevy_elapsed_time, duration);
                         self.state = PlayingState::Paused;
                     },
                 }
@@ -204,6 +278,14 @@ impl MoviePlayerStateController {
         self.loop_mode = loop_mode;
     }
 
+    pub fn get_play_range(&self) -> Option<(Duration, Duration)> {
+        self.play_range
+    }
+
+    pub fn set_play_range(&mut self, play_range: Option<(Duration, Duration)>) {
+        self.play_range = play_range;
+    }
+
 }
 
 pub trait MoviePlayer {
@@ -211,9 +293,17 @@ pub trait MoviePlayer {
     fn get_state_controller_mut(&mut self) -> &mut MoviePlayerStateController;
     fn get_duration(&self) -> Duration;
     fn get_resolution(&self) -> (u32, u32);
+    fn get_metadata(&self) -> MovieMetadata {
+        MovieMetadata::default()
+    }
     fn set_volume(&mut self, _volume: f32);
     fn get_volume(&self) -> f32;
 
+    /// live stream has unknown duration, and can't seek
+    fn is_live(&self) -> bool {
+        false
+    }
+
     fn play(&mut self) {
         let state_controller = self.get_state_controller_mut();
         state_controller.play(state_controller.bevy_elapsed_time);
@@ -256,6 +346,29 @@ pub trait MoviePlayer {
     fn set_loop_mode(&mut self, loop_mode: LoopMode) {
         self.get_state_controller_mut().set_loop_mode(loop_mode);
     }
+
+    /// Play from `start`, and treat `end` as the end of movie for `LoopMode`
+    fn play_range(&mut self, start: Duration, end: Duration) {
+        let duration = self.get_duration();
+        let start = start.min(duration);
+        let end = end.min(duration).max(start);
+
+        if self.get_state() != PlayingState::Stopped {
+            self.stop();
+        }
+        self.get_state_controller_mut().set_play_range(Some((start, end)));
+        let _ = self.seek(start);
+        self.play();
+    }
+
+    /// Back to the whole movie (playing state is kept)
+    fn clear_play_range(&mut self) {
+        self.get_state_controller_mut().set_play_range(None);
+    }
+
+    fn get_play_range(&self) -> Option<(Duration, Duration)> {
+        self.get_state_controller().get_play_range()
+    }
 }
 
 pub trait StateChecker {
@@ -2,10 +2,25 @@ use bevy::prelude::*;
 
 #[cfg(feature = "ffmpeg")]
 use crate::ffmpeg::{FFmpegMovie, FFmpegMovieLoader};
+#[cfg(feature = "ffmpeg")]
+use crate::ffmpeg_stream::{FFmpegStream, FFmpegStreamLoader};
 #[cfg(feature = "gv")]
-use crate::gv::{GVMovie, GVMovieLoader, GVMovieOnMemory, GVMovieOnMemoryLoader};
+use crate::gv::{send_gv_frame_decode_errors, FrameDecodeError, GVMovie, GVMovieLoader};
 #[cfg(feature = "lottie")]
 use crate::lottie::{LottieMovie, LottieMovieLoader};
+use crate::movie_format::{AnyMovie, AnyMovieLoader, MovieAssetRoot, MovieExtensions};
+#[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+use crate::subtitle::{update_movie_subtitle_positions, update_movie_subtitles, SubtitleCueChanged, SubtitleTrack, SubtitleTrackLoader};
+
+/// Plugin to register movie assets and loaders.
+///
+/// Extensions claimed by each backend are read from `MovieExtensions` resource (default if not inserted):
+///
+/// ```ignore
+/// app.insert_resource(MovieExtensions::default().add_ffmpeg("ts").add_ffmpeg("flv"))
+///     .add_plugins(MoviePlayerPlugin);
+/// ```
+#[derive(Default)]
 pub struct MoviePlayerPlugin;
 
 // fn hello_world() {
@@ -14,22 +29,57 @@ pub struct MoviePlayerPlugin;
 
 impl Plugin for MoviePlayerPlugin {
     fn build(&self, app: &mut App) {
+        let extensions = app.world().get_resource::<MovieExtensions>().cloned().unwrap_or_default();
+        let asset_root = app.world().get_resource::<MovieAssetRoot>().cloned().unwrap_or_default();
+        app.insert_resource(extensions.clone())
+            .insert_resource(asset_root.clone());
+
         #[cfg(feature = "gv")]
-        app
-            .init_asset::<GVMovie>()
-            .init_asset::<GVMovieOnMemory>()
-            .init_asset_loader::<GVMovieLoader>()
-            .init_asset_loader::<GVMovieOnMemoryLoader>()
-            ;
+        {
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            app
+                .init_asset::<GVMovie>()
+                .register_asset_loader(GVMovieLoader::with_extensions(&extensions.gv).with_asset_server(asset_server).with_asset_root(asset_root.clone()))
+                .add_message::<FrameDecodeError>()
+                .add_systems(PostUpdate, send_gv_frame_decode_errors)
+                .add_systems(PostUpdate, crate::gv_texture_array::update_gv_texture_array_images)
+                ;
+        }
         #[cfg(feature = "lottie")]
         app
             .init_asset::<LottieMovie>()
-            .init_asset_loader::<LottieMovieLoader>()
+            .register_asset_loader(LottieMovieLoader::with_extensions(&extensions.lottie))
             ;
+        #[cfg(feature = "dotlottie")]
+        app
+            .init_asset::<crate::dotlottie::DotLottie>()
+            .init_asset_loader::<crate::dotlottie::DotLottieLoader>()
+            ;
+        #[cfg(all(feature = "lottie", feature = "ui"))]
+        app.add_systems(PostUpdate, crate::lottie::update_lottie_auto_render_sizes.after(bevy::ui::UiSystems::Layout));
         #[cfg(feature = "ffmpeg")]
         app
             .init_asset::<FFmpegMovie>()
-            .init_asset_loader::<FFmpegMovieLoader>()
+            .register_asset_loader(FFmpegMovieLoader::with_extensions(&extensions.ffmpeg).with_asset_root(asset_root.clone()))
+            .init_asset::<FFmpegStream>()
+            .init_asset_loader::<FFmpegStreamLoader>()
+            ;
+
+        // subtitles follow movies, so only with a backend
+        #[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+        app
+            .init_asset::<SubtitleTrack>()
+            .init_asset_loader::<SubtitleTrackLoader>()
+            .add_message::<SubtitleCueChanged>()
+            .add_systems(PostUpdate, (update_movie_subtitle_positions, update_movie_subtitles).chain())
+            ;
+        #[cfg(all(feature = "ui", any(feature = "gv", feature = "lottie", feature = "ffmpeg")))]
+        app.add_systems(PostUpdate, crate::subtitle::update_movie_subtitle_texts.after(update_movie_subtitles));
+
+        // NOTE: registered last, so untyped loads of ambiguous extensions are sniffed
+        app
+            .init_asset::<AnyMovie>()
+            .register_asset_loader(AnyMovieLoader::with_extensions(&extensions.ambiguous()).with_asset_root(asset_root))
             ;
     }
 }
@@ -46,4 +96,14 @@ mod tests {
             .add_plugins(MoviePlayerPlugin)
             .update(); // run once
     }
+
+    #[test]
+    fn custom_extensions() {
+        App::new()
+            .add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin::default())
+            .insert_resource(MovieExtensions::default().add_ffmpeg("ts").add_gv("ts"))
+            .add_plugins(MoviePlayerPlugin)
+            .update(); // run once
+    }
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_functions,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var array_texture: texture_2d_array<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var array_texture_sampler: sampler;
+
+// unlit, the layer is `MeshTag` of the entity (synced from `GVTextureArrayLayer`)
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let layer = mesh_functions::get_tag(mesh.instance_index);
+    return textureSample(array_texture, array_texture_sampler, mesh.uv, layer);
+}
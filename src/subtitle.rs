@@ -0,0 +1,406 @@
+use bevy::asset::io::Reader;
+use bevy::asset::AssetLoader;
+use bevy::asset::LoadContext;
+use bevy::prelude::*;
+use bevy::tasks::ConditionalSendFuture;
+
+#[cfg(feature = "ffmpeg")]
+use crate::ffmpeg::FFmpegMovie;
+#[cfg(feature = "gv")]
+use crate::gv::GVMovie;
+#[cfg(feature = "lottie")]
+use crate::lottie::LottieMovie;
+#[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+use crate::movie_format::AnyMovie;
+#[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+use crate::movie_player::MoviePlayer;
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleCue {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+}
+
+/// Subtitle track (from `.srt` / `.vtt` sidecar, or embedded text subtitle stream)
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct SubtitleTrack {
+    pub language: Option<String>,
+    /// sorted by start time
+    pub cues: Vec<SubtitleCue>,
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// Parse `hh:mm:ss,ttt` (SRT) or `[hh:]mm:ss.ttt` (WebVTT)
+fn parse_timestamp(s: &str) -> Option<Duration> {
+    let s = s.trim().replace(',', ".");
+    let (hms, millis) = s.split_once('.').unwrap_or((&s, "0"));
+    let parts: Vec<&str> = hms.split(':').collect();
+    let (h, m, sec) = match parts.as_slice() {
+        [h, m, sec] => (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?, sec.parse::<u64>().ok()?),
+        [m, sec] => (0, m.parse::<u64>().ok()?, sec.parse::<u64>().ok()?),
+        _ => return None,
+    };
+    if millis.is_empty() || !millis.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // "5" -> 500ms, "05" -> 50ms, "005" -> 5ms
+    let millis_str = format!("{:0<3}", millis.chars().take(3).collect::<String>());
+    let millis = millis_str.parse::<u64>().ok()?;
+    Some(Duration::from_millis(((h * 60 + m) * 60 + sec) * 1000 + millis))
+}
+
+/// Parse `start --> end [settings]`
+fn parse_timing_line(line: &str) -> Option<(Duration, Duration)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start)?, parse_timestamp(end)?))
+}
+
+/// Remove `<b>`, `<v Name>`, `<00:01.000>` like tags and `{\an8}` like overrides
+fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut depth_angle = false;
+    let mut depth_brace = false;
+    for c in text.chars() {
+        match c {
+            '<' if !depth_brace => depth_angle = true,
+            '>' if depth_angle => depth_angle = false,
+            '{' if !depth_angle => depth_brace = true,
+            '}' if depth_brace => depth_brace = false,
+            _ if depth_angle || depth_brace => {},
+            _ => result.push(c),
+        }
+    }
+    result
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+}
+
+fn normalize_newlines(text: &str) -> String {
+    text.trim_start_matches('\u{feff}').replace("\r\n", "\n").replace('\r', "\n")
+}
+
+impl SubtitleTrack {
+    pub fn new(mut cues: Vec<SubtitleCue>) -> Self {
+        cues.sort_by_key(|cue| cue.start);
+        Self {
+            language: None,
+            cues,
+        }
+    }
+
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Parse SubRip (`.srt`) text
+    pub fn from_srt(text: &str) -> Result<Self, std::io::Error> {
+        let text = normalize_newlines(text);
+        let mut cues = vec![];
+
+        for block in text.split("\n\n") {
+            let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty()).collect();
+            let Some(timing_index) = lines.iter().position(|l| l.contains("-->")) else {
+                continue;
+            };
+            let Some((start, end)) = parse_timing_line(lines[timing_index]) else {
+                warn!("Invalid SRT timing: {}", lines[timing_index]);
+                continue;
+            };
+            cues.push(SubtitleCue {
+                start,
+                end,
+                text: strip_tags(&lines[timing_index + 1..].join("\n")),
+            });
+        }
+
+        if cues.is_empty() && !text.trim().is_empty() {
+            return Err(invalid_data("no cue found in SRT".to_string()));
+        }
+        Ok(Self::new(cues))
+    }
+
+    /// Parse WebVTT (`.vtt`) text
+    pub fn from_vtt(text: &str) -> Result<Self, std::io::Error> {
+        let text = normalize_newlines(text);
+        // "WEBVTT" alone, or followed by a space, tab or newline
+        let has_signature = text.strip_prefix("WEBVTT")
+            .is_some_and(|rest| matches!(rest.chars().next(), None | Some(' ' | '\t' | '\n')));
+        if !has_signature {
+            return Err(invalid_data("WebVTT must start with WEBVTT".to_string()));
+        }
+        let mut cues = vec![];
+
+        // NOTE: first block is header
+        for block in text.split("\n\n").skip(1) {
+            let lines: Vec<&str> = block.lines().collect();
+            let Some(first) = lines.first() else {
+                continue;
+            };
+            if first.starts_with("NOTE") || first.starts_with("STYLE") || first.starts_with("REGION") {
+                continue;
+            }
+            let Some(timing_index) = lines.iter().position(|l| l.contains("-->")) else {
+                continue;
+            };
+            let Some((start, end)) = parse_timing_line(lines[timing_index]) else {
+                warn!("Invalid WebVTT timing: {}", lines[timing_index]);
+                continue;
+            };
+            cues.push(SubtitleCue {
+                start,
+                end,
+                text: strip_tags(&lines[timing_index + 1..].join("\n")),
+            });
+        }
+
+        Ok(Self::new(cues))
+    }
+
+    /// Index of cue shown at the position
+    pub fn get_active_cue_index(&self, position: Duration) -> Option<usize> {
+        // latest cue which started before position (cues may overlap)
+        let upper = self.cues.partition_point(|cue| cue.start <= position);
+        (0..upper).rev().find(|&i| position < self.cues[i].end)
+    }
+
+    pub fn get_active_cue(&self, position: Duration) -> Option<&SubtitleCue> {
+        self.get_active_cue_index(position).map(|i| &self.cues[i])
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct SubtitleTrackLoader;
+
+impl AssetLoader for SubtitleTrackLoader {
+    type Asset = SubtitleTrack;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let text = String::from_utf8(bytes).map_err(|e| invalid_data(e.to_string()))?;
+            let is_vtt = load_context.path().path().extension().is_some_and(|ext| ext == "vtt");
+            if is_vtt {
+                SubtitleTrack::from_vtt(&text)
+            } else {
+                SubtitleTrack::from_srt(&text)
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["srt", "vtt"]
+    }
+}
+
+/// Shows the active cue of a subtitle track.
+///
+/// `position` follows the bound `movie` every frame (or set it manually when no movie is bound).
+/// If the entity has `Text` (`--features ui`), its content is replaced with the active cue.
+#[derive(Component, Debug, Default)]
+pub struct MovieSubtitle {
+    pub track: Handle<SubtitleTrack>,
+    /// movie whose position is used
+    #[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+    pub movie: Option<AnyMovie>,
+    pub position: Duration,
+    pub active_cue: Option<SubtitleCue>,
+}
+
+impl MovieSubtitle {
+    pub fn new(track: Handle<SubtitleTrack>) -> Self {
+        Self {
+            track,
+            ..default()
+        }
+    }
+
+    #[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+    pub fn with_movie(mut self, movie: AnyMovie) -> Self {
+        self.movie = Some(movie);
+        self
+    }
+
+    pub fn set_position(&mut self, position: Duration) {
+        self.position = position;
+    }
+
+    pub fn get_active_text(&self) -> &str {
+        self.active_cue.as_ref().map(|cue| cue.text.as_str()).unwrap_or("")
+    }
+}
+
+/// Sent when the active cue of `MovieSubtitle` changes (`None` on gap)
+#[derive(Message, Debug, Clone)]
+pub struct SubtitleCueChanged {
+    pub entity: Entity,
+    pub cue: Option<SubtitleCue>,
+}
+
+/// Copy the position of the bound movie to `MovieSubtitle`
+#[cfg(any(feature = "gv", feature = "lottie", feature = "ffmpeg"))]
+pub fn update_movie_subtitle_positions(
+    mut query: Query<&mut MovieSubtitle>,
+    #[cfg(feature = "gv")] gv_movies: Res<Assets<GVMovie>>,
+    #[cfg(feature = "lottie")] lottie_movies: Res<Assets<LottieMovie>>,
+    #[cfg(feature = "ffmpeg")] ffmpeg_movies: Res<Assets<FFmpegMovie>>,
+) {
+    for mut subtitle in &mut query {
+        let position = match &subtitle.movie {
+            #[cfg(feature = "gv")]
+            Some(AnyMovie::GV(handle)) => gv_movies.get(handle).map(|movie| movie.player.get_position()),
+            #[cfg(feature = "lottie")]
+            Some(AnyMovie::Lottie(handle)) => lottie_movies.get(handle).map(|movie| movie.player.get_position()),
+            #[cfg(feature = "ffmpeg")]
+            Some(AnyMovie::FFmpeg(handle)) => ffmpeg_movies.get(handle).map(|movie| movie.player.get_position()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        };
+        if let Some(position) = position {
+            if subtitle.position != position {
+                subtitle.position = position;
+            }
+        }
+    }
+}
+
+pub fn update_movie_subtitles(
+    mut query: Query<(Entity, &mut MovieSubtitle)>,
+    tracks: Res<Assets<SubtitleTrack>>,
+    mut cue_changed: MessageWriter<SubtitleCueChanged>,
+) {
+    for (entity, mut subtitle) in &mut query {
+        let Some(track) = tracks.get(&subtitle.track) else {
+            continue;
+        };
+        let cue = track.get_active_cue(subtitle.position);
+        if cue != subtitle.active_cue.as_ref() {
+            let cue = cue.cloned();
+            subtitle.active_cue = cue.clone();
+            cue_changed.write(SubtitleCueChanged { entity, cue });
+        }
+    }
+}
+
+#[cfg(feature = "ui")]
+pub fn update_movie_subtitle_texts(
+    mut query: Query<(&MovieSubtitle, &mut Text), Changed<MovieSubtitle>>,
+) {
+    for (subtitle, mut text) in &mut query {
+        if text.0 != subtitle.get_active_text() {
+            text.0 = subtitle.get_active_text().to_string();
+        }
+    }
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\n<i>World</i>\r\nline 2\r\n";
+        let track = SubtitleTrack::from_srt(srt).unwrap();
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].start, Duration::from_millis(1000));
+        assert_eq!(track.cues[0].end, Duration::from_millis(2500));
+        assert_eq!(track.cues[1].text, "World\nline 2");
+
+        assert_eq!(track.get_active_cue(Duration::from_millis(500)), None);
+        assert_eq!(track.get_active_cue(Duration::from_millis(1200)).unwrap().text, "Hello");
+        assert_eq!(track.get_active_cue(Duration::from_millis(2700)), None);
+        assert_eq!(track.get_active_cue_index(Duration::from_millis(3000)), Some(1));
+    }
+
+    #[test]
+    fn parse_vtt() {
+        let vtt = "WEBVTT - test\n\nNOTE comment\n\nintro\n00:01.000 --> 00:02.000 align:start\n<v Roger>Hi &amp; bye\n\n00:00:02.500 --> 00:00:03.000\nBye\n";
+        let track = SubtitleTrack::from_vtt(vtt).unwrap();
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].text, "Hi & bye");
+        assert_eq!(track.cues[1].start, Duration::from_millis(2500));
+
+        assert!(SubtitleTrack::from_vtt("1\n00:00:01,000 --> 00:00:02,000\nx").is_err());
+        assert!(SubtitleTrack::from_vtt("WEBVTTX\n\n00:01.000 --> 00:02.000\nx").is_err());
+        assert!(SubtitleTrack::from_vtt("WEBVTT").is_ok());
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("01:02:03,5"), Some(Duration::from_millis(3723500)));
+        assert_eq!(parse_timestamp("00:01.0456"), Some(Duration::from_millis(1045)));
+        // non-ASCII after the dot must not panic
+        assert_eq!(parse_timestamp("00:01.é5"), None);
+        assert_eq!(parse_timestamp("00:01."), None);
+    }
+
+    #[test]
+    fn cue_changed_message() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin::default())
+            .init_asset::<SubtitleTrack>()
+            .add_message::<SubtitleCueChanged>()
+            .add_systems(Update, update_movie_subtitles);
+
+        let track = SubtitleTrack::from_srt("1\n00:00:01,000 --> 00:00:02,000\nHello\n").unwrap();
+        let handle = app.world_mut().resource_mut::<Assets<SubtitleTrack>>().add(track);
+        let entity = app.world_mut().spawn(MovieSubtitle::new(handle)).id();
+
+        app.world_mut().get_mut::<MovieSubtitle>(entity).unwrap().set_position(Duration::from_millis(1500));
+        app.update();
+
+        let subtitle = app.world().get::<MovieSubtitle>(entity).unwrap();
+        assert_eq!(subtitle.get_active_text(), "Hello");
+        let messages = app.world().resource::<Messages<SubtitleCueChanged>>();
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[cfg(feature = "gv")]
+    #[test]
+    fn follow_movie_position() {
+        use crate::gv::try_load_gv_from_reader;
+        use crate::gv::GVReader;
+        use crate::movie_player::LoadMode;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin::default())
+            .init_asset::<SubtitleTrack>()
+            .init_asset::<GVMovie>()
+            .add_message::<SubtitleCueChanged>()
+            .add_systems(Update, (update_movie_subtitle_positions, update_movie_subtitles).chain());
+
+        let mut player = try_load_gv_from_reader(GVReader::open("assets/test.gv", LoadMode::OnMemory).unwrap()).unwrap();
+        player.play();
+        player.update(Duration::from_millis(20));
+        let movie = app.world_mut().resource_mut::<Assets<GVMovie>>().add(GVMovie { player });
+
+        let track = SubtitleTrack::from_srt("1\n00:00:00,010 --> 00:00:00,030\nHello\n").unwrap();
+        let track = app.world_mut().resource_mut::<Assets<SubtitleTrack>>().add(track);
+        let entity = app.world_mut().spawn(MovieSubtitle::new(track).with_movie(AnyMovie::GV(movie))).id();
+        app.update();
+
+        let subtitle = app.world().get::<MovieSubtitle>(entity).unwrap();
+        assert_eq!(subtitle.position, Duration::from_millis(20));
+        assert_eq!(subtitle.get_active_text(), "Hello");
+    }
+}
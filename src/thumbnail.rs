@@ -0,0 +1,293 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::color::Color;
+use bevy::color::ColorToPacked;
+use bevy::color::Srgba;
+use bevy::image::Image;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+
+use crate::blankable_image_data_provider::unpremultiply_bgra;
+use crate::blankable_image_data_provider::BGRAImageFrameProvider;
+use crate::movie_player::ImageData;
+use crate::movie_player::MoviePlayer;
+use crate::movie_player::PlayingState;
+
+use std::time::Duration;
+
+/// Grab a frame at any time (e.g. for thumbnails / posters), without changing playing state
+pub trait FrameGrabber {
+    /// uncompressed frame (straight alpha) at `time`, clamped to duration.
+    ///
+    /// NOTE: ffmpeg seeks a separate decoder, so it is slower than playing
+    fn get_frame_at(&mut self, time: Duration) -> Option<ImageData>;
+}
+
+impl<T> FrameGrabber for T
+where
+    T: MoviePlayer + BGRAImageFrameProvider
+{
+    fn get_frame_at(&mut self, time: Duration) -> Option<ImageData> {
+        let duration = self.get_duration();
+        let saved = self.get_state_controller().clone();
+
+        // pause at the time on the controller, and restore it after reading
+        let controller = self.get_state_controller_mut();
+        controller.state = PlayingState::Paused;
+        controller.seek_position = time.min(duration);
+        controller.play_started_time = Some(controller.bevy_elapsed_time);
+        controller.pause_started_time = Some(controller.bevy_elapsed_time);
+        let frame = self.get_grabbed_frame_bgra();
+        *self.get_state_controller_mut() = saved;
+
+        let mut frame = frame?;
+        let format = self.get_frame_texture_format();
+        if self.is_premultiplied_alpha() && format == TextureFormat::Bgra8UnormSrgb {
+            unpremultiply_bgra(&mut frame);
+        }
+        Some(ImageData::new(frame, format, self.get_resolution()))
+    }
+}
+
+/// Settings of `create_contact_sheet`
+#[derive(Debug, Clone)]
+pub struct ContactSheetSettings {
+    pub columns: u32,
+    pub rows: u32,
+    /// size of each cell (frames fit into it, keeping aspect ratio). `None` = movie resolution
+    pub cell_size: Option<(u32, u32)>,
+    /// pixels between cells and around the sheet
+    pub spacing: u32,
+    pub background: Color,
+}
+
+impl Default for ContactSheetSettings {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            rows: 4,
+            cell_size: Some((160, 90)),
+            spacing: 4,
+            background: Color::BLACK,
+        }
+    }
+}
+
+/// Resize BGRA8 with bilinear filter
+fn resize_bgra(data: &[u8], from: (u32, u32), to: (u32, u32)) -> Vec<u8> {
+    if from == to {
+        return data.to_vec();
+    }
+    let (fw, fh) = (from.0 as usize, from.1 as usize);
+    let mut resized = Vec::with_capacity(to.0 as usize * to.1 as usize * 4);
+    for y in 0..to.1 {
+        let sy = ((y as f32 + 0.5) * fh as f32 / to.1 as f32 - 0.5).clamp(0.0, (fh - 1) as f32);
+        let (y0, ty) = (sy.floor() as usize, sy.fract());
+        let y1 = (y0 + 1).min(fh - 1);
+        for x in 0..to.0 {
+            let sx = ((x as f32 + 0.5) * fw as f32 / to.0 as f32 - 0.5).clamp(0.0, (fw - 1) as f32);
+            let (x0, tx) = (sx.floor() as usize, sx.fract());
+            let x1 = (x0 + 1).min(fw - 1);
+            for c in 0..4 {
+                let p = |x: usize, y: usize| data[(y * fw + x) * 4 + c] as f32;
+                let top = p(x0, y0) + (p(x1, y0) - p(x0, y0)) * tx;
+                let bottom = p(x0, y1) + (p(x1, y1) - p(x0, y1)) * tx;
+                resized.push((top + (bottom - top) * ty).round() as u8);
+            }
+        }
+    }
+    resized
+}
+
+/// Sample `columns * rows` frames evenly over the movie, into a grid image (BGRA8).
+///
+/// Frames which can't be read (or aren't BGRA8, e.g. HDR output) are left as background.
+pub fn create_contact_sheet<P: FrameGrabber + MoviePlayer>(player: &mut P, settings: &ContactSheetSettings) -> Image {
+    let resolution = player.get_resolution();
+    let (cell_width, cell_height) = settings.cell_size.unwrap_or(resolution);
+    let (columns, rows) = (settings.columns.max(1), settings.rows.max(1));
+    let spacing = settings.spacing;
+    let width = columns * cell_width + (columns + 1) * spacing;
+    let height = rows * cell_height + (rows + 1) * spacing;
+
+    let [r, g, b, a] = Srgba::from(settings.background).to_u8_array();
+    let mut data = [b, g, r, a].repeat((width * height) as usize);
+
+    let count = columns * rows;
+    let duration = player.get_duration().as_secs_f64();
+    for i in 0..count {
+        // center of each section, not to hit black frames at the start / end
+        let time = Duration::from_secs_f64(duration * (i as f64 + 0.5) / count as f64);
+        let Some(frame) = player.get_frame_at(time) else {
+            continue;
+        };
+        if frame.format != TextureFormat::Bgra8UnormSrgb || frame.get_width() == 0 || frame.get_height() == 0 {
+            continue;
+        }
+
+        // fit into the cell, keeping aspect ratio
+        let scale = (cell_width as f64 / frame.get_width() as f64).min(cell_height as f64 / frame.get_height() as f64);
+        let fit_width = ((frame.get_width() as f64 * scale).round() as u32).clamp(1, cell_width);
+        let fit_height = ((frame.get_height() as f64 * scale).round() as u32).clamp(1, cell_height);
+        let resized = resize_bgra(&frame.data, frame.resolution, (fit_width, fit_height));
+
+        let x0 = spacing + (i % columns) * (cell_width + spacing) + (cell_width - fit_width) / 2;
+        let y0 = spacing + (i / columns) * (cell_height + spacing) + (cell_height - fit_height) / 2;
+        let row_bytes = fit_width as usize * 4;
+        for y in 0..fit_height as usize {
+            let dst = ((y0 as usize + y) * width as usize + x0 as usize) * 4;
+            data[dst..dst + row_bytes].copy_from_slice(&resized[y * row_bytes..(y + 1) * row_bytes]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+// test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movie_player::MoviePlayerStateController;
+
+    /// 4x2 frames filled with the frame number (10fps, 1s)
+    #[derive(Default)]
+    struct CountingPlayer {
+        state_controller: MoviePlayerStateController,
+    }
+
+    impl MoviePlayer for CountingPlayer {
+        fn get_state_controller(&self) -> &MoviePlayerStateController {
+            &self.state_controller
+        }
+
+        fn get_state_controller_mut(&mut self) -> &mut MoviePlayerStateController {
+            &mut self.state_controller
+        }
+
+        fn get_duration(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        fn get_resolution(&self) -> (u32, u32) {
+            (4, 2)
+        }
+
+        fn set_volume(&mut self, _volume: f32) {}
+
+        fn get_volume(&self) -> f32 {
+            0.0
+        }
+    }
+
+    impl CountingPlayer {
+        fn frame(&self) -> Option<Vec<u8>> {
+            let frame_num = (self.get_position().as_secs_f64() * 10.0).min(9.0) as u8;
+            Some([frame_num, frame_num, frame_num, 255].repeat(8))
+        }
+    }
+
+    impl BGRAImageFrameProvider for CountingPlayer {
+        fn get_first_frame_bgra(&mut self) -> Option<Vec<u8>> {
+            Some([0, 0, 0, 255].repeat(8))
+        }
+
+        fn get_paused_frame_bgra(&mut self) -> Option<Vec<u8>> {
+            self.frame()
+        }
+
+        fn get_playing_frame_bgra(&mut self) -> Option<Vec<u8>> {
+            self.frame()
+        }
+
+        fn get_last_frame_bgra(&mut self) -> Option<Vec<u8>> {
+            Some([9, 9, 9, 255].repeat(8))
+        }
+    }
+
+    #[test]
+    fn frame_at_keeps_state() {
+        let mut player = CountingPlayer::default();
+        player.get_state_controller_mut().bevy_elapsed_time = Duration::from_secs(5);
+        player.play();
+        player.get_state_controller_mut().bevy_elapsed_time = Duration::from_secs_f64(5.25);
+
+        let frame = player.get_frame_at(Duration::from_secs_f64(0.75)).unwrap();
+        assert_eq!(frame.resolution, (4, 2));
+        assert_eq!(frame.data[0], 7);
+        assert_eq!(player.get_state(), PlayingState::Playing);
+        assert_eq!(player.get_position(), Duration::from_secs_f64(0.25));
+    }
+
+    #[test]
+    fn contact_sheet() {
+        let mut player = CountingPlayer::default();
+        let settings = ContactSheetSettings {
+            columns: 2,
+            rows: 2,
+            cell_size: Some((2, 2)),
+            spacing: 1,
+            background: Color::WHITE,
+        };
+        let image = create_contact_sheet(&mut player, &settings);
+        assert_eq!(image.size(), bevy::math::UVec2::new(7, 7));
+
+        let data = image.data.unwrap();
+        let pixel = |x: usize, y: usize| &data[(y * 7 + x) * 4..(y * 7 + x) * 4 + 4];
+        assert_eq!(pixel(0, 0), &[255, 255, 255, 255]);
+        // frames at 0.125s, 0.375s, 0.625s, 0.875s (4x2 fit into 2x1, centered vertically)
+        assert_eq!(pixel(1, 1), &[1, 1, 1, 255]);
+        assert_eq!(pixel(4, 1), &[3, 3, 3, 255]);
+        assert_eq!(pixel(1, 4), &[6, 6, 6, 255]);
+        assert_eq!(pixel(4, 4), &[8, 8, 8, 255]);
+        assert_eq!(player.get_state(), PlayingState::Stopped);
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    #[test]
+    fn ffmpeg_frame_at_keeps_decoder() {
+        use std::process::Command;
+        use std::process::Stdio;
+
+        // 64x48, 10fps, 1s
+        let path = std::env::temp_dir().join(format!("bevy_movie_player_grab_{}.mkv", std::process::id()));
+        let status = Command::new("ffmpeg")
+            .args(["-loglevel", "quiet", "-y",
+                "-f", "lavfi", "-i", "testsrc=size=64x48:rate=10", "-t", "1", "-c:v", "ffv1"])
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if !status.is_ok_and(|status| status.success()) {
+            eprintln!("ffmpeg command not found, skipped");
+            return;
+        }
+
+        let mut player = crate::ffmpeg::load_movie(path.to_str().unwrap());
+        player.play();
+        player.update(Duration::from_millis(500));
+        let playing = player.get_playing_frame_bgra().unwrap();
+        let decoder_frame_number = player.decoder_frame_number;
+
+        let frame = player.get_frame_at(Duration::from_millis(100)).unwrap();
+        assert_eq!(frame.resolution, (64, 48));
+        assert_eq!(frame.data.len(), 64 * 48 * 4);
+
+        // playback decoder and cached frame are untouched
+        assert_eq!(player.decoder_frame_number, decoder_frame_number);
+        assert_eq!(player.cached_frame.as_ref(), Some(&playing));
+        assert_eq!(player.get_state(), PlayingState::Playing);
+        assert_eq!(player.get_position(), Duration::from_millis(500));
+
+        let _ = std::fs::remove_file(path);
+    }
+}